@@ -0,0 +1,399 @@
+use clap::{Parser, Subcommand};
+use cosmwasm_std::{Decimal, Uint128};
+use entropy_beacon_cosmos::{beacon::UpdateConfigMsg, provide::BeaconConfigResponse};
+
+use crate::{
+    cosmos::response::TxResponse,
+    cosmos::tx::TxError,
+    utils::{beacon_interface::Beacon, CLITheme},
+};
+
+use super::{
+    instantiate::{validate_settings, InstantiateError},
+    project_config::load_project,
+};
+
+#[derive(Debug, Parser, Clone)]
+pub struct AdminCommandOptions {
+    /// Optional path to the configuration file
+    #[clap(short, long)]
+    #[clap(default_value = "entropy.json")]
+    pub config: String,
+    /// Network to use (defined in config). Optional if default network is set in config
+    #[clap(short, long)]
+    pub network: Option<String>,
+    /// Wallet to use (defined in config), must be the owner of the beacon. Optional if default wallet is set in config
+    #[clap(short, long)]
+    pub wallet: Option<String>,
+    /// Only show the changes, without sending them
+    #[clap(long, global = true)]
+    pub dry_run: bool,
+    /// Send the changes without asking for confirmation
+    #[clap(short, long, global = true)]
+    pub yes: bool,
+    #[clap(subcommand)]
+    pub command: AdminCommand,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum AdminCommand {
+    #[clap(about = "Update the beacon configuration, leaving unset fields unchanged")]
+    UpdateConfig(UpdateConfigOptions),
+    #[clap(about = "Update the gas price the beacon uses to calculate fees")]
+    UpdateGasPrice {
+        /// New gas price, in the native denom of the beacon
+        gas_price: Decimal,
+    },
+}
+
+#[derive(Debug, Parser, Clone)]
+pub struct UpdateConfigOptions {
+    /// Amount that must be deposited to whitelist a new public key
+    #[clap(long)]
+    pub whitelist_deposit_amt: Option<Uint128>,
+    /// Amount of the deposit that unlocks with each submission of entropy
+    #[clap(long)]
+    pub refund_increment_amt: Option<Uint128>,
+    /// Blocks before a whitelisted public key can be used to submit entropy
+    #[clap(long)]
+    pub key_activation_delay: Option<u64>,
+    /// Fee charged on top of the requested gas fees
+    #[clap(long)]
+    pub protocol_fee: Option<u64>,
+    /// Percentage of the protocol fee paid to the wallet submitting entropy
+    #[clap(long)]
+    pub submitter_share: Option<u64>,
+    /// Native currency of the chain
+    #[clap(long)]
+    pub native_denom: Option<String>,
+    /// Whether the beacon is paused
+    #[clap(long)]
+    pub paused: Option<bool>,
+    /// Whether the beacon is in permissioned mode
+    #[clap(long)]
+    pub permissioned: Option<bool>,
+    /// Whether callbacks are subsidized
+    #[clap(long)]
+    pub subsidize_callbacks: Option<bool>,
+}
+
+impl From<UpdateConfigOptions> for UpdateConfigMsg {
+    fn from(options: UpdateConfigOptions) -> Self {
+        UpdateConfigMsg {
+            whitelist_deposit_amt: options.whitelist_deposit_amt,
+            refund_increment_amt: options.refund_increment_amt,
+            key_activation_delay: options.key_activation_delay,
+            protocol_fee: options.protocol_fee,
+            submitter_share: options.submitter_share,
+            native_denom: options.native_denom,
+            paused: options.paused,
+            permissioned: options.permissioned,
+            subsidize_callbacks: options.subsidize_callbacks,
+        }
+    }
+}
+
+pub async fn admin_cmd(options: AdminCommandOptions) {
+    let theme = CLITheme::default();
+    let (_, network, wallet) = load_project(
+        &options.config,
+        options.network.as_ref(),
+        options.wallet.clone(),
+    );
+    let beacon_address = network.deployed_beacon_address.clone().unwrap_or_else(|| {
+        println!(
+            "{}",
+            theme
+                .error
+                .apply_to("No deployed beacon found in config file, please deploy a beacon first.")
+        );
+        std::process::exit(1);
+    });
+    let beacon = Beacon::new(network, wallet, beacon_address);
+
+    // Both are needed to check the config that results from the update.
+    let current = beacon.fetch_config().await.unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Error fetching beacon config:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+    let current_gas_price = beacon
+        .calculate_fee(0)
+        .await
+        .unwrap_or_else(|e| {
+            println!(
+                "{} {}",
+                theme.error.apply_to("Error fetching beacon gas price:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        })
+        .gas_price;
+
+    let changes = command_changes(&current, current_gas_price, &options.command);
+
+    if changes.is_empty() {
+        println!("{}", theme.dimmed.apply_to("Nothing to update."));
+        return;
+    }
+    for change in &changes {
+        println!(
+            "{} {} {} {}",
+            theme.highlight.apply_to(format!("{}:", change.name)),
+            theme.error.apply_to(&change.current),
+            theme.dimmed.apply_to("->"),
+            theme.success.apply_to(&change.proposed)
+        );
+    }
+    if let Err(e) = validate_update(&current, current_gas_price, &options.command) {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Invalid beacon config:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    }
+    if options.dry_run {
+        println!("{}", theme.dimmed.apply_to("Dry run, no changes sent."));
+        return;
+    }
+    if !options.yes
+        && !dialoguer::Confirm::with_theme(&theme)
+            .with_prompt("Apply these changes?")
+            .default(false)
+            .interact()
+            .unwrap()
+    {
+        return;
+    }
+
+    let res: Result<TxResponse, TxError> = match options.command {
+        AdminCommand::UpdateConfig(update) => beacon.update_config(update.into()).await,
+        AdminCommand::UpdateGasPrice { gas_price } => beacon.update_gas_price(gas_price).await,
+    };
+    match res {
+        Ok(res) => println!(
+            "{} {}",
+            theme.success.apply_to("Beacon updated successfully!"),
+            theme.highlight.apply_to(res.txhash)
+        ),
+        Err(e) => {
+            println!(
+                "{} {}",
+                theme.error.apply_to("Error updating beacon:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Fields a command would change, with their current and proposed values.
+fn command_changes(
+    current: &BeaconConfigResponse,
+    current_gas_price: Decimal,
+    command: &AdminCommand,
+) -> Vec<Change> {
+    match command {
+        AdminCommand::UpdateConfig(update) => config_diff(current, &update.clone().into()),
+        AdminCommand::UpdateGasPrice { gas_price } => {
+            if current_gas_price == *gas_price {
+                vec![]
+            } else {
+                vec![Change {
+                    name: "gas_price",
+                    current: current_gas_price.to_string(),
+                    proposed: gas_price.to_string(),
+                }]
+            }
+        }
+    }
+}
+
+/// Checks the beacon config that results from applying a command to the current one.
+fn validate_update(
+    current: &BeaconConfigResponse,
+    current_gas_price: Decimal,
+    command: &AdminCommand,
+) -> Result<(), InstantiateError> {
+    let (update, gas_price) = match command {
+        AdminCommand::UpdateConfig(update) => (Some(update), current_gas_price),
+        AdminCommand::UpdateGasPrice { gas_price } => (None, *gas_price),
+    };
+    let current_share = (current.submitter_share * Uint128::new(100)).u128();
+    validate_settings(
+        update
+            .and_then(|u| u.submitter_share)
+            .unwrap_or_else(|| u64::try_from(current_share).unwrap_or(u64::MAX)),
+        update
+            .and_then(|u| u.whitelist_deposit_amt)
+            .unwrap_or(current.whitelist_deposit_amt),
+        update
+            .and_then(|u| u.refund_increment_amt)
+            .unwrap_or(current.refund_increment_amt),
+        gas_price,
+        update
+            .and_then(|u| u.native_denom.as_deref())
+            .unwrap_or(&current.native_denom),
+    )
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Change {
+    name: &'static str,
+    current: String,
+    proposed: String,
+}
+
+/// Fields an update would change, with their current and proposed values.
+fn config_diff(current: &BeaconConfigResponse, update: &UpdateConfigMsg) -> Vec<Change> {
+    // The beacon reports the submitter share as a fraction, but is updated with a percentage.
+    let submitter_share = current.submitter_share * Uint128::new(100);
+    let fields = [
+        (
+            "whitelist_deposit_amt",
+            current.whitelist_deposit_amt.to_string(),
+            update.whitelist_deposit_amt.map(|v| v.to_string()),
+        ),
+        (
+            "refund_increment_amt",
+            current.refund_increment_amt.to_string(),
+            update.refund_increment_amt.map(|v| v.to_string()),
+        ),
+        (
+            "key_activation_delay",
+            current.key_activation_delay.to_string(),
+            update.key_activation_delay.map(|v| v.to_string()),
+        ),
+        (
+            "protocol_fee",
+            current.protocol_fee.to_string(),
+            update.protocol_fee.map(|v| v.to_string()),
+        ),
+        (
+            "submitter_share",
+            submitter_share.to_string(),
+            update.submitter_share.map(|v| v.to_string()),
+        ),
+        (
+            "native_denom",
+            current.native_denom.clone(),
+            update.native_denom.clone(),
+        ),
+        (
+            "paused",
+            current.paused.to_string(),
+            update.paused.map(|v| v.to_string()),
+        ),
+        (
+            "permissioned",
+            current.permissioned.to_string(),
+            update.permissioned.map(|v| v.to_string()),
+        ),
+        (
+            "subsidize_callbacks",
+            current.subsidize_callbacks.to_string(),
+            update.subsidize_callbacks.map(|v| v.to_string()),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(name, current, proposed)| {
+            proposed
+                .filter(|proposed| *proposed != current)
+                .map(|proposed| Change {
+                    name,
+                    current,
+                    proposed,
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn current() -> BeaconConfigResponse {
+        BeaconConfigResponse {
+            whitelist_deposit_amt: Uint128::new(1000),
+            refund_increment_amt: Uint128::new(100),
+            key_activation_delay: 0,
+            protocol_fee: 4125,
+            submitter_share: Decimal::from_str("0.8").unwrap(),
+            native_denom: "uluna".to_string(),
+            paused: false,
+            permissioned: false,
+            test_mode: true,
+            subsidize_callbacks: false,
+        }
+    }
+
+    fn update_config(args: &[&str]) -> AdminCommand {
+        let options = UpdateConfigOptions::parse_from(
+            std::iter::once("update-config").chain(args.iter().copied()),
+        );
+        AdminCommand::UpdateConfig(options)
+    }
+
+    #[test]
+    fn updates_are_validated_against_the_current_config() {
+        let gas_price = Decimal::from_str("0.15").unwrap();
+        let valid = |command| validate_update(&current(), gas_price, &command).is_ok();
+        assert!(valid(update_config(&["--submitter-share", "100"])));
+        assert!(!valid(update_config(&["--submitter-share", "101"])));
+        assert!(valid(update_config(&["--refund-increment-amt", "1000"])));
+        assert!(!valid(update_config(&["--whitelist-deposit-amt", "10"])));
+        assert!(!valid(update_config(&["--native-denom", "!"])));
+        assert!(!valid(AdminCommand::UpdateGasPrice {
+            gas_price: Decimal::zero()
+        }));
+        assert!(valid(AdminCommand::UpdateGasPrice {
+            gas_price: Decimal::one()
+        }));
+    }
+
+    #[test]
+    fn diff_only_lists_changed_fields() {
+        let current = BeaconConfigResponse {
+            whitelist_deposit_amt: Uint128::zero(),
+            refund_increment_amt: Uint128::zero(),
+            key_activation_delay: 0,
+            protocol_fee: 4125,
+            submitter_share: Decimal::from_str("0.8").unwrap(),
+            native_denom: "uluna".to_string(),
+            paused: false,
+            permissioned: false,
+            test_mode: true,
+            subsidize_callbacks: false,
+        };
+        let update = UpdateConfigMsg {
+            whitelist_deposit_amt: None,
+            refund_increment_amt: None,
+            key_activation_delay: Some(10),
+            protocol_fee: Some(4125),
+            submitter_share: Some(80),
+            native_denom: None,
+            paused: Some(true),
+            permissioned: None,
+            subsidize_callbacks: Some(false),
+        };
+        let change = |name, current: &str, proposed: &str| Change {
+            name,
+            current: current.to_string(),
+            proposed: proposed.to_string(),
+        };
+        assert_eq!(
+            config_diff(&current, &update),
+            vec![
+                change("key_activation_delay", "0", "10"),
+                change("paused", "false", "true"),
+            ]
+        );
+    }
+}
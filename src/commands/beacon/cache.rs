@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use clap::{Parser, Subcommand};
+
+use crate::utils::{wasm_cache::WasmCache, CLITheme};
+
+#[derive(Debug, Parser, Clone)]
+pub struct CacheCommandOptions {
+    #[clap(subcommand)]
+    pub command: CacheCommand,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum CacheCommand {
+    #[clap(about = "List cached beacon WASM files")]
+    List,
+    #[clap(about = "Remove cached beacon WASM files")]
+    Clear {
+        /// Only remove the files of this release
+        #[clap(long)]
+        beacon_version: Option<String>,
+    },
+    #[clap(about = "Add a beacon WASM file to the cache, to deploy it offline")]
+    Add {
+        /// Path to the WASM file
+        file: String,
+        /// Release the file is cached as, e.g. `v1.0.0`
+        #[clap(long)]
+        beacon_version: String,
+    },
+}
+
+pub fn cache_cmd(options: CacheCommandOptions) {
+    let theme = CLITheme::default();
+    let cache = WasmCache::open().unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Error opening cache:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+
+    let res = match options.command {
+        CacheCommand::List => cache.list().map(|cached| {
+            if cached.is_empty() {
+                println!("{}", theme.dimmed.apply_to("The cache is empty."));
+            }
+            for cached in cached {
+                println!(
+                    "{} {} {}",
+                    theme.highlight.apply_to(&cached.tag),
+                    cached.checksum,
+                    theme.dimmed.apply_to(format!("({} bytes)", cached.size))
+                );
+            }
+        }),
+        CacheCommand::Clear { beacon_version } => {
+            cache.clear(beacon_version.as_deref()).map(|removed| {
+                println!(
+                    "{}",
+                    theme
+                        .success
+                        .apply_to(format!("Removed {removed} cached file(s)."))
+                );
+            })
+        }
+        CacheCommand::Add {
+            file,
+            beacon_version,
+        } => cache.add(&beacon_version, Path::new(&file)).map(|cached| {
+            println!(
+                "{} {} {}",
+                theme.success.apply_to("Cached release"),
+                theme.highlight.apply_to(&cached.tag),
+                cached.checksum
+            );
+        }),
+    };
+    if let Err(e) = res {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Error updating cache:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    }
+}
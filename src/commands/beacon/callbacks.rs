@@ -0,0 +1,224 @@
+//! Reports the outcome of the entropy callbacks triggered by a submission.
+//!
+//! Callbacks are executed by the beacon as messages to the callback contracts of
+//! the fulfilled requests, in the order the request ids were submitted. Their
+//! outcome is read from the events of the submission:
+//!
+//! * An `execute` event for the callback contract means the callback ran, and the
+//!   `wasm` attributes following its `_contract_address` are the ones it emitted.
+//! * A callback without an `execute` event is reported as failed, with the message
+//!   log or raw log of the transaction when the chain returns a plain error there.
+//! * A failed submission reverts every callback, so each request is reported with
+//!   the error of the transaction.
+//!
+//! The beacon does not report errors or gas usage per callback, so the only gas
+//! figure available is the `gas_used` of the whole transaction.
+
+use cosmwasm_std::Uint128;
+use entropy_beacon_cosmos::provide::ActiveRequestInfo;
+
+use crate::cosmos::{response::TxResponse, tx::TxError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallbackOutcome {
+    Succeeded,
+    Failed(String),
+    /// The submission failed, so the callback was reverted or never ran.
+    Reverted(String),
+    /// The transaction has no logs to tell from, which some nodes omit.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackReport {
+    pub request_id: Uint128,
+    pub callback_address: String,
+    pub outcome: CallbackOutcome,
+    pub gas_limit: u64,
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Attributes emitted by a single contract execution.
+#[derive(Debug, Default)]
+struct Execution {
+    contract: String,
+    attributes: Vec<(String, String)>,
+}
+
+/// Splits the events of the given type into executions, each starting at a
+/// `_contract_address` attribute.
+fn executions(res: &TxResponse, event_type: &str) -> Vec<Execution> {
+    let mut executions = Vec::<Execution>::new();
+    let events = res.logs.iter().flat_map(|log| &log.events).filter(|event| {
+        event.type_ == event_type || event.type_.starts_with(&format!("{event_type}-"))
+    });
+    for event in events {
+        for (key, value) in &event.ordered_attributes {
+            if key == "_contract_address" {
+                executions.push(Execution {
+                    contract: value.clone(),
+                    ..Default::default()
+                });
+            } else if let Some(execution) = executions.last_mut() {
+                execution.attributes.push((key.clone(), value.clone()));
+            }
+        }
+    }
+    executions
+}
+
+/// The error the chain returned for a transaction, if any. Successful
+/// transactions normally only log their events, as JSON, so that is skipped.
+fn chain_error(res: &TxResponse) -> Option<&str> {
+    res.logs
+        .iter()
+        .map(|log| log.log.trim())
+        .chain(std::iter::once(res.raw_log.trim()))
+        .find(|log| !log.is_empty() && !log.starts_with(['[', '{']))
+}
+
+pub fn callback_reports(res: &TxResponse, requests: &[ActiveRequestInfo]) -> Vec<CallbackReport> {
+    let executed = executions(res, "execute");
+    let emitted = executions(res, "wasm");
+
+    let mut seen = Vec::<&str>::new();
+    requests
+        .iter()
+        .map(|request| {
+            let callback_address = request.callback_address.as_str();
+            // Requests sharing a callback contract are called back in order
+            let nth = seen.iter().filter(|a| **a == callback_address).count();
+            seen.push(callback_address);
+
+            let ran = executed
+                .iter()
+                .filter(|e| e.contract == callback_address)
+                .nth(nth)
+                .is_some();
+            let outcome = if ran {
+                CallbackOutcome::Succeeded
+            } else if res.logs.is_empty() {
+                CallbackOutcome::Unknown
+            } else {
+                CallbackOutcome::Failed(match chain_error(res) {
+                    Some(error) => format!("Callback was not executed: {error}"),
+                    None => "Callback was not executed".to_string(),
+                })
+            };
+            let attributes = emitted
+                .iter()
+                .filter(|e| e.contract == callback_address)
+                .nth(nth)
+                .filter(|_| ran)
+                .map(|e| e.attributes.clone())
+                .unwrap_or_default();
+
+            CallbackReport {
+                request_id: request.id,
+                callback_address: callback_address.to_string(),
+                outcome,
+                gas_limit: request.callback_gas_limit,
+                attributes,
+            }
+        })
+        .collect()
+}
+
+/// Reports the callbacks of a submission that failed, with the error of the
+/// transaction, or of the broadcast if it never made it on chain.
+pub fn failed_reports(error: &TxError, requests: &[ActiveRequestInfo]) -> Vec<CallbackReport> {
+    let error = match error {
+        TxError::TxFailed(res) => res.raw_log.clone(),
+        e => e.to_string(),
+    };
+    requests
+        .iter()
+        .map(|request| CallbackReport {
+            request_id: request.id,
+            callback_address: request.callback_address.to_string(),
+            outcome: CallbackOutcome::Reverted(error.clone()),
+            gas_limit: request.callback_gas_limit,
+            attributes: vec![],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::Addr;
+
+    use super::*;
+
+    fn request(id: u128, callback: &str) -> ActiveRequestInfo {
+        ActiveRequestInfo {
+            id: Uint128::new(id),
+            callback_gas_limit: 100_000,
+            callback_address: Addr::unchecked(callback),
+            submitter: Addr::unchecked("submitter"),
+            submitted_block_height: 0,
+            submitted_bounty_amount: Uint128::zero(),
+        }
+    }
+
+    fn event(type_: &str, attributes: &[(&str, &str)]) -> serde_json::Value {
+        let attributes = attributes
+            .iter()
+            .map(|(k, v)| serde_json::json!({"key": k, "value": v}))
+            .collect::<Vec<_>>();
+        serde_json::json!({"type": type_, "attributes": attributes})
+    }
+
+    #[test]
+    fn reports_are_read_from_merged_events() {
+        let res = serde_json::json!({
+            "code": 0, "codespace": "", "data": "", "gas_wanted": "0", "gas_used": "0",
+            "height": "1", "info": "", "txhash": "hash", "timestamp": "",
+            "logs": [{
+                "msg_index": 0, "log": "",
+                "events": [
+                    event("execute", &[
+                        ("_contract_address", "beacon"),
+                        ("_contract_address", "consumer"),
+                    ]),
+                    event("wasm", &[
+                        ("_contract_address", "beacon"),
+                        ("action", "submit_entropy"),
+                        ("_contract_address", "consumer"),
+                        ("action", "receive_entropy"),
+                        ("action", "roll"),
+                    ]),
+                ],
+            }],
+        });
+        let mut res = serde_json::from_value::<TxResponse>(res).unwrap();
+        let requests = [request(1, "consumer"), request(2, "other")];
+        let reports = callback_reports(&res, &requests);
+
+        assert_eq!(reports[0].outcome, CallbackOutcome::Succeeded);
+        assert_eq!(
+            reports[0].attributes,
+            vec![
+                ("action".to_string(), "receive_entropy".to_string()),
+                ("action".to_string(), "roll".to_string()),
+            ]
+        );
+        assert_eq!(
+            reports[1].outcome,
+            CallbackOutcome::Failed("Callback was not executed".to_string())
+        );
+        assert!(reports[1].attributes.is_empty());
+
+        res.logs[0].log = "callback other failed".to_string();
+        assert_eq!(
+            callback_reports(&res, &requests)[1].outcome,
+            CallbackOutcome::Failed("Callback was not executed: callback other failed".to_string())
+        );
+
+        res.raw_log = "out of gas".to_string();
+        let reports = failed_reports(&TxError::TxFailed(Box::new(res)), &requests);
+        assert_eq!(reports.len(), 2);
+        assert!(reports
+            .iter()
+            .all(|r| r.outcome == CallbackOutcome::Reverted("out of gas".to_string())));
+    }
+}
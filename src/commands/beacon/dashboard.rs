@@ -0,0 +1,527 @@
+use std::collections::{HashSet, VecDeque};
+
+use cosmrs::tx::Gas;
+use cosmwasm_std::Uint128;
+use dialoguer::console::{pad_str, truncate_str, Alignment, Key, Term};
+use ecvrf_rs::{encode_hex, Proof, SecretKey};
+use entropy_beacon_cosmos::provide::ActiveRequestInfo;
+use rand::Rng;
+use tokio::sync::mpsc;
+
+use crate::utils::{beacon_interface::Beacon, CLITheme};
+
+use super::{
+    callbacks::{callback_reports, failed_reports, CallbackOutcome, CallbackReport},
+    dev::{seeded_entropy, sign_proof, test_proof},
+    faults::FaultOptions,
+};
+
+/// How many submissions are kept on screen.
+const RECENT_SUBMISSIONS: usize = 5;
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+struct Submission {
+    request_ids: Vec<Uint128>,
+    result: Result<String, String>,
+    reports: Vec<CallbackReport>,
+}
+
+/// What the user asked for with a key press.
+#[derive(Debug, PartialEq, Eq)]
+enum Action {
+    None,
+    Quit,
+    /// Submit random, seeded or signed entropy for the given requests
+    Submit(Vec<Uint128>),
+    /// Submit the given entropy for the given requests
+    SubmitEntropy(Vec<Uint128>, Vec<u8>),
+}
+
+#[derive(Default)]
+struct Dashboard {
+    requests: Vec<ActiveRequestInfo>,
+    cursor: usize,
+    selected: HashSet<u128>,
+    auto: bool,
+    /// Whether proofs are signed, which determines the entropy so it can't be typed in
+    proofs: bool,
+    /// Hex entropy being typed in for a manual submission
+    input: Option<String>,
+    last_entropy: Option<String>,
+    submissions: VecDeque<Submission>,
+    status: String,
+}
+
+impl Dashboard {
+    fn set_requests(&mut self, requests: Vec<ActiveRequestInfo>) {
+        self.selected
+            .retain(|id| requests.iter().any(|r| r.id.u128() == *id));
+        self.cursor = self.cursor.min(requests.len().saturating_sub(1));
+        self.requests = requests;
+    }
+
+    /// Selected requests, or the one under the cursor if none are selected.
+    fn targets(&self) -> Vec<Uint128> {
+        if self.selected.is_empty() {
+            self.requests
+                .get(self.cursor)
+                .map(|r| r.id)
+                .into_iter()
+                .collect()
+        } else {
+            self.requests
+                .iter()
+                .filter(|r| self.selected.contains(&r.id.u128()))
+                .map(|r| r.id)
+                .collect()
+        }
+    }
+
+    fn push_submission(&mut self, submission: Submission) {
+        self.status = match &submission.result {
+            Ok(_) => "Entropy submitted successfully!".to_string(),
+            Err(_) => "Error submitting entropy".to_string(),
+        };
+        // Fulfilled requests are no longer active
+        if submission.result.is_ok() {
+            for id in &submission.request_ids {
+                self.selected.remove(&id.u128());
+            }
+            let mut requests = std::mem::take(&mut self.requests);
+            requests.retain(|r| !submission.request_ids.contains(&r.id));
+            self.set_requests(requests);
+        }
+        self.submissions.push_front(submission);
+        self.submissions.truncate(RECENT_SUBMISSIONS);
+    }
+
+    fn handle_key(&mut self, key: &Key) -> Action {
+        if let Some(input) = &mut self.input {
+            match key {
+                Key::Char(c) if c.is_ascii_hexdigit() => input.push(*c),
+                Key::Backspace => {
+                    input.pop();
+                }
+                Key::Escape => self.input = None,
+                Key::Enter => {
+                    let input = self.input.take().unwrap_or_default();
+                    return match hex::decode(&input) {
+                        Ok(entropy) if !entropy.is_empty() => {
+                            Action::SubmitEntropy(self.targets(), entropy)
+                        }
+                        _ => {
+                            self.status = "Entropy must be non-empty, even length hex".to_string();
+                            Action::None
+                        }
+                    };
+                }
+                _ => {}
+            }
+            return Action::None;
+        }
+
+        match key {
+            Key::ArrowUp | Key::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            Key::ArrowDown | Key::Char('j') => {
+                self.cursor = (self.cursor + 1).min(self.requests.len().saturating_sub(1));
+            }
+            Key::Char(' ') => {
+                if let Some(request) = self.requests.get(self.cursor) {
+                    if !self.selected.remove(&request.id.u128()) {
+                        self.selected.insert(request.id.u128());
+                    }
+                }
+            }
+            Key::Char('s') | Key::Enter => return Action::Submit(self.targets()),
+            Key::Char('m') if self.proofs => {
+                self.status = "Entropy is determined by signed proofs, use s to submit".to_string();
+            }
+            Key::Char('m') => self.input = Some(String::new()),
+            Key::Char('a') => self.auto = !self.auto,
+            Key::Char('q') | Key::Escape => return Action::Quit,
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn render(&self, beacon_address: &str, width: usize) -> Vec<String> {
+        let theme = CLITheme::default();
+        let mut lines = vec![];
+        let fit = |s: &str| truncate_str(s, width, "…").to_string();
+        let column = |s: &str, w: usize| {
+            pad_str(&truncate_str(s, w, "…"), w, Alignment::Left, None).to_string()
+        };
+
+        lines.push(format!(
+            "{} {}",
+            dialoguer::console::style(format!("entropy beacon dev v{}", env!("CARGO_PKG_VERSION")))
+                .bold(),
+            if self.auto {
+                theme.success.apply_to("[auto-submit on]")
+            } else {
+                theme.dimmed.apply_to("[auto-submit off]")
+            }
+        ));
+        lines.push(fit(&format!("Beacon: {beacon_address}")));
+        lines.push(fit(&format!(
+            "Last entropy: {}",
+            self.last_entropy.as_deref().unwrap_or("unknown")
+        )));
+        lines.push(String::new());
+
+        lines.push(
+            theme
+                .highlight
+                .apply_to(format!("Active requests ({})", self.requests.len()))
+                .to_string(),
+        );
+        lines.push(
+            theme
+                .dimmed
+                .apply_to(format!(
+                    "      {}{}{}{}{}",
+                    column("ID", 8),
+                    column("Submitter", 24),
+                    column("Callback", 24),
+                    column("Gas limit", 12),
+                    "Bounty"
+                ))
+                .to_string(),
+        );
+        for (i, request) in self.requests.iter().enumerate() {
+            let line = fit(&format!(
+                "{} [{}] {}{}{}{}{}",
+                if i == self.cursor { '>' } else { ' ' },
+                if self.selected.contains(&request.id.u128()) {
+                    'x'
+                } else {
+                    ' '
+                },
+                column(&request.id.to_string(), 8),
+                column(request.submitter.as_str(), 24),
+                column(request.callback_address.as_str(), 24),
+                column(&request.callback_gas_limit.to_string(), 12),
+                request.submitted_bounty_amount
+            ));
+            lines.push(if i == self.cursor {
+                theme.highlight.apply_to(line).to_string()
+            } else {
+                line
+            });
+        }
+        lines.push(String::new());
+
+        lines.extend(self.render_submissions(width));
+        lines.push(String::new());
+
+        if let Some(input) = &self.input {
+            lines.push(fit(&format!("Entropy (hex): {input}_")));
+            lines.push(
+                theme
+                    .dimmed
+                    .apply_to("enter submit  esc cancel")
+                    .to_string(),
+            );
+        } else {
+            let help = if self.proofs {
+                "↑/↓ move  space select  s submit  a toggle auto  q quit"
+            } else {
+                "↑/↓ move  space select  s submit  m manual entropy  a toggle auto  q quit"
+            };
+            lines.push(fit(&self.status));
+            lines.push(fit(&theme.dimmed.apply_to(help).to_string()));
+        }
+        lines
+    }
+
+    fn render_submissions(&self, width: usize) -> Vec<String> {
+        let theme = CLITheme::default();
+        let mut lines = vec![];
+        let fit = |s: &str| truncate_str(s, width, "…").to_string();
+
+        lines.push(theme.highlight.apply_to("Recent submissions").to_string());
+        for submission in &self.submissions {
+            let ids = submission
+                .request_ids
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(fit(&match &submission.result {
+                Ok(txhash) => format!("{} [{ids}] {txhash}", theme.success.apply_to("✔")),
+                Err(e) => format!("{} [{ids}] {e}", theme.error.apply_to("✘")),
+            }));
+            for report in &submission.reports {
+                let outcome = match &report.outcome {
+                    CallbackOutcome::Succeeded => theme.success.apply_to("succeeded".to_string()),
+                    CallbackOutcome::Failed(e) => theme.error.apply_to(format!("failed: {e}")),
+                    CallbackOutcome::Reverted(e) => theme.error.apply_to(format!("reverted: {e}")),
+                    CallbackOutcome::Unknown => theme.dimmed.apply_to("unknown".to_string()),
+                };
+                let attributes = report
+                    .attributes
+                    .iter()
+                    .map(|(k, v)| format!("{k}={v}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                lines.push(fit(&format!(
+                    "    {} -> {} {} gas limit {} {}",
+                    report.request_id,
+                    report.callback_address,
+                    outcome,
+                    report.gas_limit,
+                    theme.dimmed.apply_to(attributes)
+                )));
+            }
+        }
+        lines
+    }
+}
+
+/// Runs dev mode as a full screen dashboard, until the user quits.
+pub async fn run(
+    beacon: Beacon,
+    signing_key: Option<SecretKey>,
+    seed: Option<String>,
+    faults: FaultOptions,
+) {
+    let theme = CLITheme::default();
+    let term = Term::stdout();
+    if !term.is_term() {
+        println!(
+            "{}",
+            theme
+                .error
+                .apply_to("Dashboard mode needs an interactive terminal.")
+        );
+        std::process::exit(1);
+    }
+
+    // Keys are read on their own thread, as reading blocks.
+    let (keys_tx, mut keys) = mpsc::unbounded_channel();
+    let key_term = term.clone();
+    std::thread::spawn(move || {
+        while let Ok(key) = key_term.read_key() {
+            if keys_tx.send(key).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut dashboard = Dashboard {
+        proofs: signing_key.is_some(),
+        ..Default::default()
+    };
+    term.hide_cursor().ok();
+    let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+    loop {
+        let action = tokio::select! {
+            _ = refresh.tick() => {
+                match beacon.fetch_active_requests().await {
+                    Ok(res) => dashboard.set_requests(res.requests),
+                    Err(e) => dashboard.status = format!("Error fetching active requests: {e}"),
+                }
+                if let Ok(res) = beacon.fetch_last_entropy().await {
+                    dashboard.last_entropy = Some(res.entropy);
+                }
+                if dashboard.auto && !dashboard.requests.is_empty() {
+                    Action::Submit(dashboard.requests.iter().map(|r| r.id).collect())
+                } else {
+                    Action::None
+                }
+            }
+            Some(key) = keys.recv() => dashboard.handle_key(&key),
+        };
+
+        match action {
+            Action::None => {}
+            Action::Quit => break,
+            Action::Submit(ids) | Action::SubmitEntropy(ids, _) if ids.is_empty() => {
+                dashboard.status = "No requests to submit".to_string();
+            }
+            Action::Submit(ids) => {
+                let requests = requests(&dashboard.requests, &ids);
+                // Seeded entropy is per request, so each request is submitted on its own.
+                let batches = if seed.is_some() {
+                    requests.into_iter().map(|r| vec![r]).collect()
+                } else {
+                    vec![requests]
+                };
+                for batch in batches {
+                    let proof = proof(
+                        &beacon,
+                        signing_key.as_ref(),
+                        seed.as_deref(),
+                        &faults,
+                        &batch,
+                    )
+                    .await;
+                    let submission = match proof {
+                        Ok(proof) => submit(&beacon, &batch, &proof, &faults).await,
+                        Err(e) => Submission {
+                            request_ids: batch.iter().map(|r| r.id).collect(),
+                            result: Err(format!("Error creating proof: {e}")),
+                            reports: vec![],
+                        },
+                    };
+                    dashboard.push_submission(submission);
+                }
+            }
+            Action::SubmitEntropy(ids, entropy) => {
+                let requests = requests(&dashboard.requests, &ids);
+                let submission = submit(&beacon, &requests, &test_proof(entropy), &faults).await;
+                dashboard.push_submission(submission);
+            }
+        }
+
+        let (rows, cols) = term.size();
+        let lines = dashboard.render(&beacon.address, usize::from(cols));
+        term.clear_screen().ok();
+        term.write_str(
+            &lines
+                .into_iter()
+                .take(usize::from(rows))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+        .ok();
+    }
+
+    term.clear_screen().ok();
+    term.show_cursor().ok();
+    // The key reading thread blocks until the next key press, so exit instead of returning.
+    std::process::exit(0);
+}
+
+/// Creates the proof for a batch of requests: signed if a signing key is set,
+/// seeded from the first request if a seed is set, random otherwise.
+async fn proof(
+    beacon: &Beacon,
+    signing_key: Option<&SecretKey>,
+    seed: Option<&str>,
+    faults: &FaultOptions,
+    batch: &[ActiveRequestInfo],
+) -> Result<Proof, String> {
+    if let Some(sk) = signing_key {
+        sign_proof(beacon, sk).await.map(|(proof, _)| proof)
+    } else if let Some(seed) = seed {
+        Ok(test_proof(seeded_entropy(seed, batch[0].id).to_vec()))
+    } else {
+        let entropy = faults.entropy().unwrap_or_else(|| {
+            let mut entropy = [0u8; 64];
+            rand::thread_rng().fill(&mut entropy);
+            entropy
+        });
+        Ok(test_proof(entropy.to_vec()))
+    }
+}
+
+fn requests(requests: &[ActiveRequestInfo], ids: &[Uint128]) -> Vec<ActiveRequestInfo> {
+    requests
+        .iter()
+        .filter(|r| ids.contains(&r.id))
+        .cloned()
+        .collect()
+}
+
+async fn submit(
+    beacon: &Beacon,
+    requests: &[ActiveRequestInfo],
+    proof: &Proof,
+    faults: &FaultOptions,
+) -> Submission {
+    let request_ids = requests.iter().map(|r| r.id).collect::<Vec<_>>();
+    match beacon
+        .submit_entropy(
+            proof,
+            Gas::from(faults.gas(requests)),
+            request_ids.clone(),
+            None,
+        )
+        .await
+    {
+        Ok(res) => Submission {
+            reports: callback_reports(&res, requests),
+            result: Ok(format!(
+                "{} gas {}/{} entropy {}",
+                res.txhash,
+                res.gas_used,
+                res.gas_wanted,
+                encode_hex(&proof.message_bytes)
+            )),
+            request_ids,
+        },
+        Err(e) => Submission {
+            request_ids,
+            reports: failed_reports(&e, requests),
+            result: Err(e.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::Addr;
+
+    use super::*;
+
+    fn request(id: u128) -> ActiveRequestInfo {
+        ActiveRequestInfo {
+            id: Uint128::new(id),
+            callback_gas_limit: 100_000,
+            callback_address: Addr::unchecked("callback"),
+            submitter: Addr::unchecked("submitter"),
+            submitted_block_height: 0,
+            submitted_bounty_amount: Uint128::zero(),
+        }
+    }
+
+    #[test]
+    fn keys_select_and_submit_requests() {
+        let mut dashboard = Dashboard::default();
+        dashboard.set_requests(vec![request(1), request(2), request(3)]);
+
+        assert_eq!(
+            dashboard.handle_key(&Key::Char('s')),
+            Action::Submit(vec![Uint128::new(1)])
+        );
+        dashboard.handle_key(&Key::ArrowDown);
+        dashboard.handle_key(&Key::Char(' '));
+        dashboard.handle_key(&Key::ArrowDown);
+        dashboard.handle_key(&Key::ArrowDown);
+        dashboard.handle_key(&Key::Char(' '));
+        assert_eq!(
+            dashboard.handle_key(&Key::Enter),
+            Action::Submit(vec![Uint128::new(2), Uint128::new(3)])
+        );
+
+        dashboard.handle_key(&Key::Char('m'));
+        for c in "0aff".chars() {
+            dashboard.handle_key(&Key::Char(c));
+        }
+        assert_eq!(
+            dashboard.handle_key(&Key::Enter),
+            Action::SubmitEntropy(vec![Uint128::new(2), Uint128::new(3)], vec![0x0a, 0xff])
+        );
+
+        dashboard.set_requests(vec![request(1)]);
+        assert!(dashboard.selected.is_empty());
+        assert_eq!(dashboard.cursor, 0);
+        assert_eq!(dashboard.handle_key(&Key::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn manual_entropy_is_disabled_with_proofs() {
+        let mut dashboard = Dashboard {
+            proofs: true,
+            ..Default::default()
+        };
+        dashboard.set_requests(vec![request(1)]);
+        dashboard.handle_key(&Key::Char('m'));
+        assert!(dashboard.input.is_none());
+        assert_eq!(
+            dashboard.handle_key(&Key::Enter),
+            Action::Submit(vec![Uint128::new(1)])
+        );
+    }
+}
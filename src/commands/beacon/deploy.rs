@@ -1,7 +1,8 @@
 use clap::Parser;
 
+use super::instantiate::InstantiateOptions;
 use crate::{
-    utils::{config::ConfigType, CLITheme},
+    utils::CLITheme,
     utils::{config::ConfigUtils, deploy::deploy_beacon},
 };
 
@@ -20,6 +21,14 @@ pub struct DeployCommandOptions {
     /// Optional path to the wasm file to deploy, if not provided will download from github
     #[clap(long)]
     pub wasm: Option<String>,
+    /// Code id of an already uploaded beacon contract to instantiate, skipping the upload
+    #[clap(long, conflicts_with = "wasm")]
+    pub code_id: Option<u64>,
+    /// Release of the beacon to download, e.g. `v1.0.0`. Defaults to `beacon_version` in the config file, or the latest release
+    #[clap(long, conflicts_with_all = &["wasm", "code_id"])]
+    pub beacon_version: Option<String>,
+    #[clap(flatten)]
+    pub instantiate: Box<InstantiateOptions>,
 }
 
 pub async fn deploy_cmd(options: DeployCommandOptions) {
@@ -33,25 +42,16 @@ pub async fn deploy_cmd(options: DeployCommandOptions) {
         .bold()
     );
 
-    let config = ConfigUtils::load(&options.config).unwrap_or_else(|e| {
-        println!(
-            "{} {}",
-            theme.error.apply_to("Error loading config file: "),
-            theme.error.apply_to(e.to_string())
-        );
-        std::process::exit(1);
-    });
-    let mut config = if let ConfigType::Project(config) = config {
-        config
-    } else {
-        println!(
-            "{}",
-            theme.error.apply_to("Config file is not a project config")
-        );
-        std::process::exit(1);
-    };
-
-    deploy_beacon(options.network, options.wallet, options.wasm, &mut config).await;
+    let config = deploy_beacon(
+        &options.config,
+        options.network,
+        options.wallet,
+        options.wasm,
+        options.code_id,
+        options.beacon_version,
+        &options.instantiate,
+    )
+    .await;
 
     ConfigUtils::save(&config, &options.config).unwrap_or_else(|e| {
         println!(
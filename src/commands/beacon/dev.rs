@@ -1,21 +1,29 @@
+use std::{
+    collections::HashSet,
+    io::{BufRead, BufReader},
+};
+
 use clap::Parser;
 use cosmrs::tx::Gas;
 use cosmwasm_std::Uint128;
 use dialoguer::Select;
-use ecvrf_rs::Proof;
-use entropy_beacon_cosmos::beacon::BEACON_BASE_GAS;
+use ecvrf_rs::{decode_hex, encode_hex, Proof, SecretKey};
+use entropy_beacon_cosmos::provide::ActiveRequestInfo;
 use rand::Rng;
+use sha2::{Digest, Sha512};
 
-use crate::{
-    cosmos::{network::Network, wallet::Wallet},
-    utils::{
-        beacon_interface::{test_pk, Beacon},
-        config::{ConfigType, ConfigUtils},
-        CLITheme,
-    },
+use crate::utils::{
+    beacon_interface::{test_pk, test_sk, Beacon},
+    vrf::verify_proof,
+    CLITheme,
 };
 
-use super::project_config::ProjectConfig;
+use super::{
+    callbacks::{callback_reports, failed_reports, CallbackOutcome, CallbackReport},
+    dashboard,
+    faults::FaultOptions,
+    project_config::load_project,
+};
 
 #[derive(Debug, Parser, Clone)]
 pub struct DevCommandOptions {
@@ -29,6 +37,39 @@ pub struct DevCommandOptions {
     /// Wallet to use (defined in config). Optional if default wallet is set in config
     #[clap(short, long)]
     pub wallet: Option<String>,
+    /// Submit genuine ECVRF proofs chained from the last entropy, as production workers do,
+    /// so the beacon does not need to be in test mode
+    #[clap(short, long)]
+    pub proofs: bool,
+    /// Secret key used to sign proofs. Defaults to the `dev_key` in config, or the test key
+    #[clap(short, long, requires = "proofs")]
+    pub key: Option<String>,
+    /// Mode to run in, prompted for if not set
+    #[clap(short, long, value_enum)]
+    pub mode: Option<DevMode>,
+    /// File to read entropy from in scripted mode, one hex value per request, or `-` for standard input
+    #[clap(short, long, conflicts_with = "proofs")]
+    pub entropy_file: Option<String>,
+    /// Derive the entropy of each request from this seed and the request id in auto mode,
+    /// so submissions can be replayed
+    #[clap(short, long, conflicts_with_all = ["proofs", "entropy_file"])]
+    pub seed: Option<String>,
+    #[clap(flatten)]
+    pub faults: FaultOptions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DevMode {
+    /// Submit random entropy as requests come in
+    Auto,
+    /// Prompt for the entropy of each submission
+    Manual,
+    /// Print incoming requests without submitting entropy
+    Watch,
+    /// Submit entropy read from the entropy file, one value per request
+    Scripted,
+    /// Full screen dashboard of active requests and submissions
+    Dashboard,
 }
 
 #[allow(clippy::too_many_lines)]
@@ -40,7 +81,36 @@ pub async fn dev_cmd(options: DevCommandOptions) {
     );
     let theme = CLITheme::default();
 
-    let (_config, network, wallet) = init_dev_cmd(options.clone());
+    let (config, network, wallet) = load_project(
+        &options.config,
+        options.network.as_ref(),
+        options.wallet.clone(),
+    );
+
+    let signing_key = options.proofs.then(|| {
+        options.key.as_ref().map_or_else(
+            || config.dev_key.unwrap_or_else(test_sk),
+            |key| {
+                let bytes = hex::decode(key)
+                    .ok()
+                    .filter(|b| b.len() == 32)
+                    .unwrap_or_else(|| {
+                        println!("{}", theme.error.apply_to("Invalid secret key."));
+                        std::process::exit(1);
+                    });
+                SecretKey::from_slice(&bytes)
+            },
+        )
+    });
+    if let Some(sk) = &signing_key {
+        println!(
+            "{} {}",
+            theme.dimmed.apply_to("Signing proofs with key"),
+            theme
+                .highlight
+                .apply_to(sk.extract_public_key_and_scalar().unwrap().0)
+        );
+    }
 
     let beacon_address = network.deployed_beacon_address.clone().unwrap_or_else(|| {
         println!(
@@ -54,24 +124,104 @@ pub async fn dev_cmd(options: DevCommandOptions) {
 
     let beacon = Beacon::new(network.clone(), wallet.clone(), beacon_address);
 
-    let mode = Select::with_theme(&theme)
-        .with_prompt("Select mode")
-        .items(&[
-            "Auto-submit Entropy",
-            "Manual-submit Entropy",
-            "Fetch Active Requests",
-        ])
-        .default(0)
-        .interact()
-        .unwrap();
+    let mode = options
+        .mode
+        .or_else(|| options.entropy_file.as_ref().map(|_| DevMode::Scripted))
+        .or_else(|| options.seed.as_ref().map(|_| DevMode::Auto))
+        .unwrap_or_else(|| {
+            let modes = [
+                DevMode::Auto,
+                DevMode::Manual,
+                DevMode::Watch,
+                DevMode::Dashboard,
+            ];
+            let mode = Select::with_theme(&theme)
+                .with_prompt("Select mode")
+                .items(&[
+                    "Auto-submit Entropy",
+                    "Manual-submit Entropy",
+                    "Fetch Active Requests",
+                    "Dashboard",
+                ])
+                .default(0)
+                .interact()
+                .unwrap();
+            modes[mode]
+        });
+
+    if options.seed.is_some() && !matches!(mode, DevMode::Auto | DevMode::Dashboard) {
+        println!(
+            "{}",
+            theme
+                .error
+                .apply_to("--seed can only be used in auto and dashboard modes.")
+        );
+        std::process::exit(1);
+    }
+
+    if options.faults.fixed_entropy.is_some() && !matches!(mode, DevMode::Auto | DevMode::Dashboard)
+    {
+        println!(
+            "{}",
+            theme
+                .error
+                .apply_to("--fixed-entropy can only be used in auto and dashboard modes.")
+        );
+        std::process::exit(1);
+    }
+    if mode == DevMode::Watch && options.faults.any() {
+        println!(
+            "{}",
+            theme
+                .error
+                .apply_to("Fault options cannot be used in watch mode, which submits no entropy.")
+        );
+        std::process::exit(1);
+    }
+
+    if mode == DevMode::Dashboard && options.faults.schedules() {
+        println!(
+            "{}",
+            theme.error.apply_to(
+                "--delay-blocks, --order, --individually and --drop-rate cannot be used in dashboard mode, where requests are picked by hand."
+            )
+        );
+        std::process::exit(1);
+    }
+
+    let mut script = (mode == DevMode::Scripted).then(|| {
+        let path = options.entropy_file.clone().unwrap_or_else(|| {
+            println!(
+                "{}",
+                theme
+                    .error
+                    .apply_to("Scripted mode requires an entropy file, set with --entropy-file.")
+            );
+            std::process::exit(1);
+        });
+        EntropyScript::open(&path).unwrap_or_else(|e| {
+            println!(
+                "{} {}",
+                theme.error.apply_to("Error opening entropy file:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        })
+    });
+
+    if mode == DevMode::Dashboard {
+        dashboard::run(beacon, signing_key, options.seed, options.faults).await;
+        return;
+    }
 
     println!("{}\n", theme.dimmed.apply_to("Starting dev mode..."));
 
     let mut seen_requests = vec![];
+    let mut dropped_requests = HashSet::new();
 
     loop {
         std::thread::sleep(std::time::Duration::from_millis(200));
-        let active_requests = beacon
+        let mut active_requests = beacon
             .fetch_active_requests()
             .await
             .unwrap_or_else(|e| {
@@ -85,131 +235,163 @@ pub async fn dev_cmd(options: DevCommandOptions) {
             .requests;
 
         match mode {
-            0 => {
+            DevMode::Auto => {
                 if active_requests.is_empty() {
                     continue;
                 }
 
-                let request_ids = active_requests
-                    .iter()
-                    .map(|r| r.id)
-                    .collect::<Vec<Uint128>>();
+                // Seeded entropy is per request, so each request is submitted on its own.
+                let Some(batches) = plan(
+                    &beacon,
+                    &options.faults,
+                    active_requests,
+                    options.seed.is_some(),
+                    &mut dropped_requests,
+                )
+                .await
+                else {
+                    continue;
+                };
 
-                println!(
-                    "Submitting entropy for requests: [{}]",
-                    request_ids
-                        .iter()
-                        .map(std::string::ToString::to_string)
-                        .collect::<Vec<String>>()
-                        .join(", ")
-                );
+                for batch in batches {
+                    print_submitting(&batch);
 
-                let mut rng = rand::thread_rng();
-                let mut entropy = [0u8; 64];
-                rng.fill(&mut entropy);
+                    let proof = if let Some(sk) = &signing_key {
+                        match signed_proof(&beacon, sk).await {
+                            Ok(proof) => proof,
+                            Err(e) => {
+                                println!("{} {}", theme.error.apply_to("Error creating proof:"), e);
+                                break;
+                            }
+                        }
+                    } else if let Some(seed) = &options.seed {
+                        let entropy = seeded_entropy(seed, batch[0].id);
+                        println!(
+                            "Entropy: \"{}\" (seed \"{}\", request {})",
+                            encode_hex(&entropy),
+                            seed,
+                            batch[0].id
+                        );
+                        test_proof(entropy.to_vec())
+                    } else {
+                        let entropy = options.faults.entropy().unwrap_or_else(|| {
+                            let mut entropy = [0u8; 64];
+                            rand::thread_rng().fill(&mut entropy);
+                            entropy
+                        });
 
-                let proof = Proof {
-                    signer: test_pk(),
-                    message_bytes: entropy.to_vec(),
-                    proof_bytes: vec![],
-                };
+                        println!("Entropy: \"{}\"", encode_hex(&entropy));
 
-                print!("Entropy: \"");
-                for x in entropy {
-                    print!("{:02x}", x);
-                }
-                println!("\"");
-
-                let total_callback_gas = BEACON_BASE_GAS
-                    + active_requests
-                        .iter()
-                        .map(|r| r.callback_gas_limit)
-                        .sum::<u64>();
-
-                let res = beacon
-                    .submit_entropy(&proof, Gas::from(total_callback_gas), request_ids, None)
-                    .await;
-                if res.is_err() {
-                    println!(
-                        "{} {}",
-                        theme.error.apply_to("Error submitting entropy:"),
-                        theme.highlight.apply_to(res.unwrap_err())
-                    );
-                    continue;
+                        test_proof(entropy.to_vec())
+                    };
+
+                    submit(&beacon, &batch, &proof, &options.faults).await;
                 }
-                let res = res.unwrap();
-                println!(
-                    "{} {}\n",
-                    theme.success.apply_to("Entropy submitted successfully!"),
-                    theme.highlight.apply_to(res.txhash.to_string())
-                );
             }
-            1 => {
+            DevMode::Manual => {
                 if active_requests.is_empty() {
                     continue;
                 }
 
-                let request_ids = active_requests
-                    .iter()
-                    .map(|r| r.id)
-                    .collect::<Vec<Uint128>>();
+                let Some(batches) = plan(
+                    &beacon,
+                    &options.faults,
+                    active_requests,
+                    false,
+                    &mut dropped_requests,
+                )
+                .await
+                else {
+                    continue;
+                };
 
-                println!(
-                    "Submitting entropy for requests: [{}]",
-                    request_ids
-                        .iter()
-                        .map(std::string::ToString::to_string)
-                        .collect::<Vec<String>>()
-                        .join(", ")
-                );
+                for batch in batches {
+                    print_submitting(&batch);
 
-                let entropy = dialoguer::Input::with_theme(&theme)
-                    .with_prompt("Enter entropy (hex)")
-                    .validate_with(|input: &String| {
-                        if !input.chars().all(|c| c.is_ascii_hexdigit()) {
-                            return Err("Entropy must be hex".to_string());
+                    let proof = if let Some(sk) = &signing_key {
+                        // Real proofs determine the entropy, so it can only be confirmed.
+                        if !dialoguer::Confirm::with_theme(&theme)
+                            .with_prompt("Submit entropy?")
+                            .default(true)
+                            .interact()
+                            .unwrap()
+                        {
+                            continue;
                         }
-                        if !input.len() % 2 == 0 {
-                            return Err("Entropy must be even length".to_string());
+                        match signed_proof(&beacon, sk).await {
+                            Ok(proof) => proof,
+                            Err(e) => {
+                                println!("{} {}", theme.error.apply_to("Error creating proof:"), e);
+                                continue;
+                            }
                         }
-                        Ok(())
-                    })
-                    .interact()
-                    .unwrap();
-                let entropy = hex::decode(entropy).unwrap();
-
-                let proof = Proof {
-                    signer: test_pk(),
-                    message_bytes: entropy.clone(),
-                    proof_bytes: vec![],
-                };
+                    } else {
+                        let entropy = dialoguer::Input::with_theme(&theme)
+                            .with_prompt("Enter entropy (hex)")
+                            .validate_with(|input: &String| {
+                                if !input.chars().all(|c| c.is_ascii_hexdigit()) {
+                                    return Err("Entropy must be hex".to_string());
+                                }
+                                if !input.len() % 2 == 0 {
+                                    return Err("Entropy must be even length".to_string());
+                                }
+                                Ok(())
+                            })
+                            .interact()
+                            .unwrap();
+                        let entropy = hex::decode(entropy).unwrap();
 
-                println!("\tEntropy: \"{:x?}\"", entropy);
-                let total_callback_gas = BEACON_BASE_GAS
-                    + active_requests
-                        .iter()
-                        .map(|r| r.callback_gas_limit)
-                        .sum::<u64>();
-
-                let res = beacon
-                    .submit_entropy(&proof, Gas::from(total_callback_gas), request_ids, None)
-                    .await;
-                if res.is_err() {
-                    println!(
-                        "{} {}",
-                        theme.error.apply_to("Error submitting entropy:"),
-                        theme.highlight.apply_to(res.unwrap_err())
-                    );
+                        println!("\tEntropy: \"{:x?}\"", entropy);
+
+                        test_proof(entropy)
+                    };
+
+                    submit(&beacon, &batch, &proof, &options.faults).await;
+                }
+            }
+            DevMode::Scripted => {
+                let script = script.as_mut().unwrap();
+                // Each request gets its own entropy value, in the order requests were made.
+                active_requests.sort_by_key(|r| r.id);
+                let Some(batches) = plan(
+                    &beacon,
+                    &options.faults,
+                    active_requests,
+                    true,
+                    &mut dropped_requests,
+                )
+                .await
+                else {
                     continue;
+                };
+                for requests in batches {
+                    let entropy = match script.next_entropy() {
+                        Ok(Some(entropy)) => entropy,
+                        Ok(None) => {
+                            println!(
+                                "{}",
+                                theme
+                                    .dimmed
+                                    .apply_to("Entropy file exhausted, exiting dev mode.")
+                            );
+                            return;
+                        }
+                        Err(e) => {
+                            println!(
+                                "{} {}",
+                                theme.error.apply_to("Error reading entropy file:"),
+                                theme.highlight.apply_to(e)
+                            );
+                            std::process::exit(1);
+                        }
+                    };
+                    print_submitting(&requests);
+                    println!("Entropy: \"{}\"", encode_hex(&entropy));
+                    submit(&beacon, &requests, &test_proof(entropy), &options.faults).await;
                 }
-                let res = res.unwrap();
-                println!(
-                    "{} {}\n",
-                    theme.success.apply_to("Entropy submitted successfully!"),
-                    theme.highlight.apply_to(res.txhash.to_string())
-                );
             }
-            2 => {
+            DevMode::Dashboard => unreachable!(),
+            DevMode::Watch => {
                 active_requests
                     .iter()
                     .filter(|r| !&seen_requests.contains(&r.id))
@@ -232,96 +414,222 @@ pub async fn dev_cmd(options: DevCommandOptions) {
                     }
                 }
             }
-            _ => unreachable!(),
         };
     }
 }
 
-fn init_dev_cmd(options: DevCommandOptions) -> (ProjectConfig, Network, Wallet) {
-    let theme = CLITheme::default();
-    let config = ConfigUtils::load(&options.config).unwrap_or_else(|e| {
-        println!(
-            "{} {}",
-            theme.error.apply_to("Error loading config file: "),
-            theme.error.apply_to(e.to_string())
-        );
-        std::process::exit(1);
-    });
-    let config = if let ConfigType::Project(config) = config {
-        config
-    } else {
-        println!(
-            "{}",
-            theme.error.apply_to("Config file is not a project config")
-        );
-        std::process::exit(1);
-    };
-    let network = match config.get_network(&options.network) {
-        Ok((_, Some(network))) => network,
-        Ok((name, None)) => {
-            println!(
-                "{} {} {}",
-                theme.error.apply_to("Network"),
-                theme.highlight.apply_to(name),
-                theme.error.apply_to("not found in config file.")
-            );
-            std::process::exit(1);
-        }
-        Err(_) => {
-            println!(
-                "{}",
-                theme.error.apply_to("No network specified. Please specify a network with the --network flag or set a default network in the config file.")
-            );
-            std::process::exit(1);
+/// Entropy values for scripted mode, one hex value per line. Empty lines and
+/// lines starting with `#` are skipped.
+struct EntropyScript {
+    lines: std::io::Lines<Box<dyn BufRead>>,
+    line: usize,
+}
+
+impl EntropyScript {
+    fn open(path: &str) -> Result<Self, std::io::Error> {
+        let reader: Box<dyn BufRead> = if path == "-" {
+            Box::new(BufReader::new(std::io::stdin()))
+        } else {
+            Box::new(BufReader::new(std::fs::File::open(path)?))
+        };
+        Ok(Self {
+            lines: reader.lines(),
+            line: 0,
+        })
+    }
+
+    fn next_entropy(&mut self) -> Result<Option<Vec<u8>>, String> {
+        for line in self.lines.by_ref() {
+            self.line += 1;
+            let line = line.map_err(|e| e.to_string())?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            return hex::decode(line)
+                .map(Some)
+                .map_err(|_| format!("invalid hex entropy on line {}", self.line));
         }
-    };
+        Ok(None)
+    }
+}
 
-    let wallet_name = options.wallet.or_else(|| config.default_wallet.clone()).unwrap_or_else(|| {
-            println!(
-                "{}",
-                theme.error.apply_to("No wallet specified. Please specify a wallet with the --wallet flag or set a default wallet in the config file.")
-            );
-            std::process::exit(1);
-        });
+/// Entropy for a request derived from a seed: `SHA-512(seed || request id as 16 big-endian bytes)`.
+pub fn seeded_entropy(seed: &str, request_id: Uint128) -> [u8; 64] {
+    Sha512::new()
+        .chain_update(seed.as_bytes())
+        .chain_update(request_id.u128().to_be_bytes())
+        .finalize()
+        .into()
+}
+
+pub fn test_proof(entropy: Vec<u8>) -> Proof {
+    Proof {
+        signer: test_pk(),
+        message_bytes: entropy,
+        proof_bytes: vec![],
+    }
+}
 
-    let wallet = config
-        .wallets
-        .as_ref()
-        .and_then(|wallets| wallets.get(&wallet_name));
+fn print_submitting(requests: &[ActiveRequestInfo]) {
+    println!(
+        "Submitting entropy for requests: [{}]",
+        requests
+            .iter()
+            .map(|r| r.id.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    );
+}
 
-    let wallet = match wallet {
-        Some(Some(mnemonic)) => Wallet::new(mnemonic.clone(), network.clone()),
-        Some(None) => {
-            let mnemonic = std::env::var("MNEMONIC").unwrap_or_else(|_| {
+/// Applies fault injection to the active requests, returning the batches to
+/// submit now. `None` if the block height needed for delays is unavailable.
+async fn plan(
+    beacon: &Beacon,
+    faults: &FaultOptions,
+    requests: Vec<ActiveRequestInfo>,
+    individually: bool,
+    dropped: &mut HashSet<u128>,
+) -> Option<Vec<Vec<ActiveRequestInfo>>> {
+    let height = if faults.needs_height() {
+        match beacon.signer.block_height().await {
+            Ok(height) => Some(u64::from(height)),
+            Err(e) => {
+                let theme = CLITheme::default();
                 println!(
-                    "{} {} {}",
-                    theme.error.apply_to("Mnemonic for wallet"),
-                    theme.highlight.apply_to(&wallet_name),
-                    theme
-                        .error
-                        .apply_to("not found in config file or MNEMONIC environment variable.")
+                    "{} {}",
+                    theme.error.apply_to("Error fetching block height:"),
+                    theme.highlight.apply_to(e)
                 );
-                std::process::exit(1);
-            });
-            Wallet::new(mnemonic, network.clone())
+                return None;
+            }
         }
-        None => {
+    } else {
+        None
+    };
+    Some(faults.plan(requests, height, individually, dropped))
+}
+
+async fn submit(
+    beacon: &Beacon,
+    requests: &[ActiveRequestInfo],
+    proof: &Proof,
+    faults: &FaultOptions,
+) {
+    let theme = CLITheme::default();
+    let total_callback_gas = faults.gas(requests);
+    let request_ids = requests.iter().map(|r| r.id).collect::<Vec<Uint128>>();
+
+    match beacon
+        .submit_entropy(proof, Gas::from(total_callback_gas), request_ids, None)
+        .await
+    {
+        Ok(res) => {
             println!(
-                "{} {} {}",
-                theme.error.apply_to("Wallet"),
-                theme.highlight.apply_to(&wallet_name),
-                theme.error.apply_to("not found in config file.")
+                "{} {}",
+                theme.success.apply_to("Entropy submitted successfully!"),
+                theme.highlight.apply_to(&res.txhash)
             );
-            std::process::exit(1);
+            println!(
+                "{} {} / {}",
+                theme.dimmed.apply_to("Gas used:"),
+                res.gas_used,
+                res.gas_wanted
+            );
+            print_callback_reports(&callback_reports(&res, requests));
+        }
+        Err(e) => {
+            println!(
+                "{} {}",
+                theme.error.apply_to("Error submitting entropy:"),
+                theme.highlight.apply_to(&e)
+            );
+            print_callback_reports(&failed_reports(&e, requests));
         }
     }
-    .unwrap_or_else(|e| {
+}
+
+fn print_callback_reports(reports: &[CallbackReport]) {
+    let theme = CLITheme::default();
+    for report in reports {
         println!(
-            "{} {}",
-            theme.error.apply_to("Error creating wallet:"),
-            theme.highlight.apply_to(e)
+            "{} {} {}",
+            theme.dimmed.apply_to("Request"),
+            theme.highlight.apply_to(report.request_id),
+            theme
+                .dimmed
+                .apply_to(format!("-> {}", report.callback_address))
         );
-        std::process::exit(1);
-    });
-    (config, network, wallet)
+        match &report.outcome {
+            CallbackOutcome::Succeeded => {
+                println!("  {}", theme.success.apply_to("Callback succeeded"));
+            }
+            CallbackOutcome::Failed(e) => println!(
+                "  {} {}",
+                theme.error.apply_to("Callback failed:"),
+                theme.highlight.apply_to(e)
+            ),
+            CallbackOutcome::Reverted(e) => println!(
+                "  {} {}",
+                theme
+                    .error
+                    .apply_to("Callback reverted, the submission failed:"),
+                theme.highlight.apply_to(e)
+            ),
+            CallbackOutcome::Unknown => println!(
+                "  {}",
+                theme
+                    .dimmed
+                    .apply_to("Callback outcome unknown, the transaction has no logs")
+            ),
+        }
+        println!(
+            "  {} {}",
+            theme.dimmed.apply_to("Gas limit:"),
+            report.gas_limit
+        );
+        for (key, value) in &report.attributes {
+            println!("  {} {}", theme.dimmed.apply_to(format!("{key}:")), value);
+        }
+    }
+    println!();
+}
+
+/// Signs a proof chained from the last entropy of the beacon, checking it
+/// locally the same way the beacon will.
+async fn signed_proof(beacon: &Beacon, sk: &SecretKey) -> Result<Proof, String> {
+    let (proof, entropy) = sign_proof(beacon, sk).await?;
+    println!("Entropy: \"{}\"", encode_hex(&entropy));
+    Ok(proof)
+}
+
+/// Signs a proof chained from the last entropy of the beacon, returning it along
+/// with the entropy it produces.
+pub async fn sign_proof(beacon: &Beacon, sk: &SecretKey) -> Result<(Proof, [u8; 64]), String> {
+    let last_entropy = beacon
+        .fetch_last_entropy()
+        .await
+        .map_err(|e| e.to_string())?;
+    let last_entropy = decode_hex(&last_entropy.entropy).map_err(|e| e.to_string())?;
+    let proof = Proof::new(sk, &last_entropy).map_err(|e| e.to_string())?;
+    let public_key = sk
+        .extract_public_key_and_scalar()
+        .map_err(|e| e.to_string())?
+        .0;
+    let entropy =
+        verify_proof(&proof, Some(&public_key), Some(&last_entropy)).map_err(|e| e.to_string())?;
+    Ok((proof, entropy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_entropy_is_reproducible_per_request() {
+        let first = seeded_entropy("seed", Uint128::new(1));
+        assert_eq!(first, seeded_entropy("seed", Uint128::new(1)));
+        assert_ne!(first, seeded_entropy("seed", Uint128::new(2)));
+        assert_ne!(first, seeded_entropy("other", Uint128::new(1)));
+    }
 }
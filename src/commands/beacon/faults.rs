@@ -0,0 +1,227 @@
+use std::collections::HashSet;
+
+use clap::Parser;
+use entropy_beacon_cosmos::{beacon::BEACON_BASE_GAS, provide::ActiveRequestInfo};
+use rand::{seq::SliceRandom, Rng};
+
+/// Misbehaviour of the beacon that dev mode can simulate, to test how consumer
+/// contracts handle it.
+#[derive(Debug, Parser, Clone, Default)]
+pub struct FaultOptions {
+    /// Only fulfil requests once they are at least this many blocks old
+    #[clap(long)]
+    pub delay_blocks: Option<u64>,
+    /// Order in which requests are fulfilled
+    #[clap(long, value_enum)]
+    pub order: Option<RequestOrder>,
+    /// Fulfil every request in its own transaction
+    #[clap(long)]
+    pub individually: bool,
+    /// Submit degenerate entropy instead of random entropy
+    #[clap(long, value_enum, conflicts_with_all = ["proofs", "seed", "entropy_file"])]
+    pub fixed_entropy: Option<FixedEntropy>,
+    /// Probability, between 0 and 1, of never fulfilling a request
+    #[clap(long, value_parser = parse_probability)]
+    pub drop_rate: Option<f64>,
+    /// Multiply the callback gas of submissions by this factor. The beacon passes each
+    /// callback the gas limit of its request, so values below 1 make the whole submission
+    /// run out of gas rather than a single callback
+    #[clap(long)]
+    pub gas_factor: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RequestOrder {
+    /// Oldest request first
+    InOrder,
+    /// Newest request first
+    Reverse,
+    /// Random order
+    Shuffle,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FixedEntropy {
+    /// Every byte is 0x00
+    Zero,
+    /// Every byte is 0xff
+    Ones,
+}
+
+fn parse_probability(s: &str) -> Result<f64, String> {
+    let p = s.parse::<f64>().map_err(|e| e.to_string())?;
+    if (0.0..=1.0).contains(&p) {
+        Ok(p)
+    } else {
+        Err("must be between 0 and 1".to_string())
+    }
+}
+
+impl FaultOptions {
+    /// Whether any fault is injected.
+    pub fn any(&self) -> bool {
+        self.delay_blocks.is_some()
+            || self.order.is_some()
+            || self.individually
+            || self.fixed_entropy.is_some()
+            || self.drop_rate.is_some()
+            || self.gas_factor.is_some()
+    }
+
+    /// Whether requests are delayed, reordered, split up or dropped before submission.
+    pub fn schedules(&self) -> bool {
+        self.delay_blocks.is_some()
+            || self.order.is_some()
+            || self.individually
+            || self.drop_rate.is_some()
+    }
+
+    pub fn needs_height(&self) -> bool {
+        self.delay_blocks.is_some()
+    }
+
+    pub fn entropy(&self) -> Option<[u8; 64]> {
+        self.fixed_entropy.map(|fixed| match fixed {
+            FixedEntropy::Zero => [0u8; 64],
+            FixedEntropy::Ones => [0xffu8; 64],
+        })
+    }
+
+    /// Total gas of a submission, with the callback gas scaled by `gas_factor`. This is
+    /// the gas of the whole transaction, the gas limit of each callback is set by its request.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn gas(&self, requests: &[ActiveRequestInfo]) -> u64 {
+        let callback_gas = requests.iter().map(|r| r.callback_gas_limit).sum::<u64>();
+        let callback_gas = self
+            .gas_factor
+            .map_or(callback_gas, |factor| (callback_gas as f64 * factor) as u64);
+        BEACON_BASE_GAS + callback_gas
+    }
+
+    /// Decides which requests to fulfil now, grouped by transaction. Requests picked
+    /// to be dropped are added to `dropped`, and never fulfilled.
+    pub fn plan(
+        &self,
+        requests: Vec<ActiveRequestInfo>,
+        height: Option<u64>,
+        individually: bool,
+        dropped: &mut HashSet<u128>,
+    ) -> Vec<Vec<ActiveRequestInfo>> {
+        let mut rng = rand::thread_rng();
+        // Requests are only considered for dropping once they are due, so each is rolled for once.
+        let mut requests = requests
+            .into_iter()
+            .filter(|r| match (self.delay_blocks, height) {
+                (Some(blocks), Some(height)) => {
+                    height.saturating_sub(r.submitted_block_height) >= blocks
+                }
+                _ => true,
+            })
+            .filter(|r| {
+                if dropped.contains(&r.id.u128()) {
+                    return false;
+                }
+                let drop = self.drop_rate.is_some_and(|rate| rng.gen_bool(rate));
+                if drop {
+                    println!("Dropping request {}", r.id);
+                    dropped.insert(r.id.u128());
+                }
+                !drop
+            })
+            .collect::<Vec<_>>();
+
+        match self.order {
+            Some(RequestOrder::InOrder) => requests.sort_by_key(|r| r.id),
+            Some(RequestOrder::Reverse) => requests.sort_by_key(|r| std::cmp::Reverse(r.id)),
+            Some(RequestOrder::Shuffle) => requests.shuffle(&mut rng),
+            None => {}
+        }
+
+        if requests.is_empty() {
+            vec![]
+        } else if individually || self.individually {
+            requests.into_iter().map(|r| vec![r]).collect()
+        } else {
+            vec![requests]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Addr, Uint128};
+
+    use super::*;
+
+    fn request(id: u128, height: u64) -> ActiveRequestInfo {
+        ActiveRequestInfo {
+            id: Uint128::new(id),
+            callback_gas_limit: 100_000,
+            callback_address: Addr::unchecked("callback"),
+            submitter: Addr::unchecked("submitter"),
+            submitted_block_height: height,
+            submitted_bounty_amount: Uint128::zero(),
+        }
+    }
+
+    fn ids(batches: &[Vec<ActiveRequestInfo>]) -> Vec<Vec<u128>> {
+        batches
+            .iter()
+            .map(|b| b.iter().map(|r| r.id.u128()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn plans_apply_delay_order_and_grouping() {
+        let requests = vec![request(1, 0), request(2, 5), request(3, 8)];
+        let faults = FaultOptions {
+            delay_blocks: Some(5),
+            order: Some(RequestOrder::Reverse),
+            ..Default::default()
+        };
+        let mut dropped = HashSet::new();
+        let plan = faults.plan(requests.clone(), Some(10), false, &mut dropped);
+        assert_eq!(ids(&plan), vec![vec![2, 1]]);
+        let plan = faults.plan(requests, Some(10), true, &mut dropped);
+        assert_eq!(ids(&plan), vec![vec![2], vec![1]]);
+    }
+
+    #[test]
+    fn dropped_requests_are_never_fulfilled() {
+        let faults = FaultOptions {
+            drop_rate: Some(1.0),
+            ..Default::default()
+        };
+        let mut dropped = HashSet::new();
+        assert!(faults
+            .plan(vec![request(1, 0)], None, false, &mut dropped)
+            .is_empty());
+        assert!(dropped.contains(&1));
+        assert!(FaultOptions::default()
+            .plan(vec![request(1, 0)], None, false, &mut dropped)
+            .is_empty());
+
+        assert_eq!(parse_probability("0.25"), Ok(0.25));
+        assert!(parse_probability("NaN").is_err());
+        assert!(parse_probability("1.5").is_err());
+        assert!(parse_probability("-0.1").is_err());
+    }
+
+    #[test]
+    fn gas_factor_scales_callback_gas() {
+        let faults = FaultOptions {
+            gas_factor: Some(0.5),
+            ..Default::default()
+        };
+        let requests = [request(1, 0), request(2, 0)];
+        assert_eq!(faults.gas(&requests), BEACON_BASE_GAS + 100_000);
+        assert_eq!(
+            FaultOptions::default().gas(&requests),
+            BEACON_BASE_GAS + 200_000
+        );
+    }
+}
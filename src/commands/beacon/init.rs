@@ -1,8 +1,8 @@
 use std::path::PathBuf;
 
 use crate::{
-    commands::beacon::project_config::ProjectConfig,
-    utils::{config::ConfigType, deploy::deploy_beacon},
+    commands::beacon::{instantiate::InstantiateOptions, project_config::ProjectConfig},
+    utils::deploy::deploy_beacon,
     utils::{config::ConfigUtils, CLITheme},
 };
 
@@ -53,35 +53,20 @@ pub async fn init_cmd(options: InitCommandOptions) {
         ProjectConfig::prompt_config_creation(&options.config);
     }
 
-    let config = ConfigUtils::load(&options.config).unwrap_or_else(|e| {
-        println!(
-            "{} {}",
-            theme.error.apply_to("Error loading config file: "),
-            theme.error.apply_to(e.to_string())
-        );
-        std::process::exit(1);
-    });
-    let mut config = if let ConfigType::Project(config) = config {
-        config
-    } else {
-        println!(
-            "{}",
-            theme.error.apply_to("Config file is not a project config")
-        );
-        std::process::exit(1);
-    };
-
     if Confirm::with_theme(&theme)
         .with_prompt("Deploy mock beacon?")
         .default(true)
         .interact()
         .unwrap()
     {
-        deploy_beacon(
+        let config = deploy_beacon(
+            &options.config,
             options.network,
             options.wallet,
             Option::<String>::None,
-            &mut config,
+            None,
+            None,
+            &InstantiateOptions::default(),
         )
         .await;
 
@@ -0,0 +1,309 @@
+use std::str::FromStr;
+
+use clap::Parser;
+use cosmrs::{AccountId, Denom};
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use ecvrf_rs::PublicKey;
+use entropy_beacon_cosmos::{beacon::BEACON_BASE_GAS, msg::InstantiateMsg};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::cosmos::network::Network;
+
+pub const DEFAULT_LABEL: &str = "Entropy Beacon (MOCK)";
+
+#[derive(Debug, Error)]
+pub enum InstantiateError {
+    #[error("Error reading instantiate file: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Invalid instantiate file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid whitelisted key {0}, expected <address>:<public key>")]
+    WhitelistedKey(String),
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// Instantiate parameters of the beacon, overriding the defaults of a mock
+/// beacon. Flags take precedence over the instantiate file.
+#[derive(Debug, Parser, Clone, Default)]
+pub struct InstantiateOptions {
+    /// JSON file with any of the fields of the beacon instantiate message, and `label`
+    #[clap(long)]
+    pub instantiate_file: Option<String>,
+    /// Label of the beacon contract
+    #[clap(long)]
+    pub label: Option<String>,
+    /// Amount that must be deposited to whitelist a new public key, defaults to 0
+    #[clap(long)]
+    pub whitelist_deposit_amt: Option<Uint128>,
+    /// Amount of the deposit that unlocks with each submission of entropy, defaults to 0
+    #[clap(long)]
+    pub refund_increment_amt: Option<Uint128>,
+    /// Blocks before a whitelisted public key can be used to submit entropy, defaults to 0
+    #[clap(long)]
+    pub key_activation_delay: Option<u64>,
+    /// Fee charged on top of the requested gas fees, defaults to the cost of the beacon's base gas
+    #[clap(long)]
+    pub protocol_fee: Option<u64>,
+    /// Percentage of the protocol fee paid to the wallet submitting entropy, defaults to 100
+    #[clap(long)]
+    pub submitter_share: Option<u64>,
+    /// Native currency of the chain, defaults to the gas denom of the network
+    #[clap(long)]
+    pub native_denom: Option<String>,
+    /// Key to whitelist at instantiation, as `<address>:<public key>`. Can be repeated
+    #[clap(long = "whitelisted-key")]
+    pub whitelisted_keys: Vec<String>,
+    /// Gas price used to calculate fees, defaults to the gas price of the network
+    #[clap(long)]
+    pub belief_gas_price: Option<Decimal>,
+    /// Only allow whitelisted keys to submit entropy, defaults to false
+    #[clap(long)]
+    pub permissioned: Option<bool>,
+    /// Accept entropy without valid proofs, defaults to true
+    #[clap(long)]
+    pub test_mode: Option<bool>,
+    /// Subsidize callbacks, defaults to the network's `subsidized_callbacks`
+    #[clap(long)]
+    pub subsidize_callbacks: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct InstantiateFile {
+    label: Option<String>,
+    whitelist_deposit_amt: Option<Uint128>,
+    refund_increment_amt: Option<Uint128>,
+    key_activation_delay: Option<u64>,
+    protocol_fee: Option<u64>,
+    submitter_share: Option<u64>,
+    native_denom: Option<String>,
+    whitelisted_keys: Option<Vec<(Addr, PublicKey)>>,
+    belief_gas_price: Option<Decimal>,
+    permissioned: Option<bool>,
+    test_mode: Option<bool>,
+    subsidize_callbacks: Option<bool>,
+}
+
+/// The instantiate message of a mock beacon on `network`, and its label.
+pub fn defaults(network: &Network) -> (InstantiateMsg, String) {
+    let subsidize_callbacks = network.subsidized_callbacks.unwrap_or(false);
+    let gas_price = Decimal::from_str(network.gas_info.gas_price.to_string().as_str()).unwrap();
+    let protocol_fee = if subsidize_callbacks {
+        Uint128::zero()
+    } else {
+        Uint128::from(BEACON_BASE_GAS) * gas_price
+    };
+    #[allow(clippy::cast_possible_truncation)]
+    let msg = InstantiateMsg {
+        whitelist_deposit_amt: Uint128::zero(),
+        refund_increment_amt: Uint128::zero(),
+        key_activation_delay: 0,
+        protocol_fee: protocol_fee.u128() as u64,
+        submitter_share: 100,
+        native_denom: network.gas_info.denom.clone(),
+        whitelisted_keys: vec![],
+        belief_gas_price: gas_price,
+        permissioned: false,
+        test_mode: true,
+        subsidize_callbacks,
+    };
+    (msg, DEFAULT_LABEL.to_string())
+}
+
+impl InstantiateOptions {
+    /// Resolves the instantiate message and label to deploy with, and validates them.
+    pub fn resolve(&self, network: &Network) -> Result<(InstantiateMsg, String), InstantiateError> {
+        let file = match &self.instantiate_file {
+            Some(path) => serde_json::from_str::<InstantiateFile>(&std::fs::read_to_string(path)?)?,
+            None => InstantiateFile::default(),
+        };
+        let whitelisted_keys = if self.whitelisted_keys.is_empty() {
+            file.whitelisted_keys
+        } else {
+            Some(
+                self.whitelisted_keys
+                    .iter()
+                    .map(|key| parse_whitelisted_key(key))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )
+        };
+
+        let (defaults, default_label) = defaults(network);
+        let msg = InstantiateMsg {
+            whitelist_deposit_amt: self
+                .whitelist_deposit_amt
+                .or(file.whitelist_deposit_amt)
+                .unwrap_or(defaults.whitelist_deposit_amt),
+            refund_increment_amt: self
+                .refund_increment_amt
+                .or(file.refund_increment_amt)
+                .unwrap_or(defaults.refund_increment_amt),
+            key_activation_delay: self
+                .key_activation_delay
+                .or(file.key_activation_delay)
+                .unwrap_or(defaults.key_activation_delay),
+            protocol_fee: self
+                .protocol_fee
+                .or(file.protocol_fee)
+                .unwrap_or(defaults.protocol_fee),
+            submitter_share: self
+                .submitter_share
+                .or(file.submitter_share)
+                .unwrap_or(defaults.submitter_share),
+            native_denom: self
+                .native_denom
+                .clone()
+                .or(file.native_denom)
+                .unwrap_or(defaults.native_denom),
+            whitelisted_keys: whitelisted_keys.unwrap_or(defaults.whitelisted_keys),
+            belief_gas_price: self
+                .belief_gas_price
+                .or(file.belief_gas_price)
+                .unwrap_or(defaults.belief_gas_price),
+            permissioned: self
+                .permissioned
+                .or(file.permissioned)
+                .unwrap_or(defaults.permissioned),
+            test_mode: self
+                .test_mode
+                .or(file.test_mode)
+                .unwrap_or(defaults.test_mode),
+            subsidize_callbacks: self
+                .subsidize_callbacks
+                .or(file.subsidize_callbacks)
+                .unwrap_or(defaults.subsidize_callbacks),
+        };
+        let label = self.label.clone().or(file.label).unwrap_or(default_label);
+
+        validate(&msg, &label)?;
+        Ok((msg, label))
+    }
+}
+
+fn parse_whitelisted_key(key: &str) -> Result<(Addr, PublicKey), InstantiateError> {
+    let invalid = || InstantiateError::WhitelistedKey(key.to_string());
+    let (address, public_key) = key.split_once(':').ok_or_else(invalid)?;
+    let public_key = hex::decode(public_key)
+        .ok()
+        .filter(|b| b.len() == 32)
+        .ok_or_else(invalid)?;
+    Ok((Addr::unchecked(address), PublicKey::from_bytes(&public_key)))
+}
+
+/// Checks the beacon settings that can be set both on instantiation and by
+/// updating the config of a deployed beacon.
+pub fn validate_settings(
+    submitter_share: u64,
+    whitelist_deposit_amt: Uint128,
+    refund_increment_amt: Uint128,
+    gas_price: Decimal,
+    native_denom: &str,
+) -> Result<(), InstantiateError> {
+    let invalid = |e: &str| Err(InstantiateError::Invalid(e.to_string()));
+    if submitter_share > 100 {
+        return invalid("The submitter share is a percentage, and cannot be over 100");
+    }
+    if refund_increment_amt > whitelist_deposit_amt {
+        return invalid("The refund increment cannot be larger than the whitelist deposit");
+    }
+    if gas_price.is_zero() {
+        return invalid("The gas price cannot be 0");
+    }
+    if Denom::from_str(native_denom).is_err() {
+        return invalid(&format!("Invalid native denom {native_denom}"));
+    }
+    Ok(())
+}
+
+fn validate(msg: &InstantiateMsg, label: &str) -> Result<(), InstantiateError> {
+    let invalid = |e: &str| Err(InstantiateError::Invalid(e.to_string()));
+    if label.trim().is_empty() {
+        return invalid("The label cannot be empty");
+    }
+    validate_settings(
+        msg.submitter_share,
+        msg.whitelist_deposit_amt,
+        msg.refund_increment_amt,
+        msg.belief_gas_price,
+        &msg.native_denom,
+    )?;
+    if let Some((address, _)) = msg
+        .whitelisted_keys
+        .iter()
+        .find(|(address, _)| AccountId::from_str(address.as_str()).is_err())
+    {
+        return invalid(&format!("Invalid whitelisted key address {address}"));
+    }
+    if msg.permissioned && msg.whitelisted_keys.is_empty() {
+        return invalid("A permissioned beacon needs at least one whitelisted key");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::beacon_interface::test_pk;
+
+    const ADDRESS: &str = "terra1x46rqay4d3cssq8gxxvqz8xt6nwlz4td20k38v";
+
+    fn network() -> Network {
+        Network::default_localterra()
+    }
+
+    #[test]
+    fn flags_override_file_and_defaults() {
+        let path = std::env::temp_dir().join(format!(
+            "entropycli-instantiate-test-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            serde_json::json!({
+                "label": "Staging Beacon",
+                "protocol_fee": 10,
+                "whitelist_deposit_amt": "1000",
+                "whitelisted_keys": [[ADDRESS, test_pk()]],
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let options = InstantiateOptions {
+            instantiate_file: Some(path.to_string_lossy().to_string()),
+            protocol_fee: Some(20),
+            test_mode: Some(false),
+            ..Default::default()
+        };
+        let (msg, label) = options.resolve(&network()).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        assert_eq!(label, "Staging Beacon");
+        assert_eq!(msg.protocol_fee, 20);
+        assert_eq!(msg.whitelist_deposit_amt, Uint128::new(1000));
+        assert_eq!(
+            msg.whitelisted_keys,
+            vec![(Addr::unchecked(ADDRESS), test_pk())]
+        );
+        assert!(!msg.test_mode);
+        assert_eq!(msg.submitter_share, 100);
+
+        let options = InstantiateOptions {
+            submitter_share: Some(101),
+            ..Default::default()
+        };
+        assert!(matches!(
+            options.resolve(&network()),
+            Err(InstantiateError::Invalid(_))
+        ));
+        let options = InstantiateOptions {
+            whitelisted_keys: vec![ADDRESS.to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            options.resolve(&network()),
+            Err(InstantiateError::WhitelistedKey(_))
+        ));
+    }
+}
@@ -1,14 +1,30 @@
 use clap::{Parser, Subcommand};
 
+pub mod admin;
+pub mod cache;
+pub mod callbacks;
+pub mod dashboard;
 pub mod deploy;
 pub mod dev;
+pub mod faults;
 pub mod init;
+pub mod instantiate;
 pub mod project_config;
+pub mod query;
+pub mod request;
+pub mod upgrade;
+pub mod verify;
 pub mod wallet;
 
+use admin::{admin_cmd, AdminCommandOptions};
+use cache::{cache_cmd, CacheCommandOptions};
 use deploy::{deploy_cmd, DeployCommandOptions};
 use dev::{dev_cmd, DevCommandOptions};
 use init::{init_cmd, InitCommandOptions};
+use query::{query_cmd, QueryCommandOptions};
+use request::{request_cmd, RequestCommandOptions};
+use upgrade::{upgrade_cmd, UpgradeCommandOptions};
+use verify::{verify_cmd, VerifyCommandOptions};
 use wallet::{wallet_cmd, WalletCommandOptions};
 
 use super::network::{network_cmd, NetworkCommandOptions};
@@ -25,10 +41,22 @@ pub enum BeaconCommand {
     Init(InitCommandOptions),
     #[clap(about = "Deploy a new instance of Beacon")]
     Deploy(DeployCommandOptions),
+    #[clap(about = "Migrate the deployed beacon to a new release")]
+    Upgrade(UpgradeCommandOptions),
+    #[clap(about = "Manage cached beacon WASM files")]
+    Cache(CacheCommandOptions),
     #[clap(about = "Manage wallets")]
     Wallet(WalletCommandOptions),
     #[clap(about = "Run a local development instance of workers")]
     Dev(DevCommandOptions),
+    #[clap(about = "Update the configuration of the deployed beacon")]
+    Admin(AdminCommandOptions),
+    #[clap(about = "Query the state of a beacon")]
+    Query(QueryCommandOptions),
+    #[clap(about = "Request entropy from the deployed beacon")]
+    Request(RequestCommandOptions),
+    #[clap(about = "Verify an entropy proof, from a transaction or JSON")]
+    Verify(VerifyCommandOptions),
     #[clap(about = "Manage networks (alias for `entropy network`)")]
     Network(NetworkCommandOptions),
 }
@@ -37,8 +65,14 @@ pub async fn beacon_cmd(options: BeaconCommandOptions) {
     match options.command {
         BeaconCommand::Init(options) => init_cmd(options).await,
         BeaconCommand::Deploy(options) => deploy_cmd(options).await,
+        BeaconCommand::Upgrade(options) => upgrade_cmd(options).await,
+        BeaconCommand::Cache(options) => cache_cmd(options),
         BeaconCommand::Wallet(options) => wallet_cmd(options),
         BeaconCommand::Dev(options) => dev_cmd(options).await,
+        BeaconCommand::Admin(options) => admin_cmd(options).await,
+        BeaconCommand::Query(options) => query_cmd(options).await,
+        BeaconCommand::Request(options) => request_cmd(options).await,
+        BeaconCommand::Verify(options) => verify_cmd(options).await,
         BeaconCommand::Network(options) => network_cmd(options),
     }
 }
@@ -1,12 +1,13 @@
 use std::{collections::HashMap, path::Path};
 
 use dialoguer::Confirm;
+use ecvrf_rs::SecretKey;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    cosmos::network::Network,
+    cosmos::{network::Network, wallet::Wallet},
     utils::{config::{ConfigUtils, Config, ConfigType}, user_prompts::create_network},
-    utils::{user_prompts::create_wallet, CLITheme},
+    utils::{contracts::ContractConfig, user_prompts::create_wallet, CLITheme},
 };
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,14 @@ pub struct ProjectConfig {
     pub default_network: Option<String>,
     pub default_wallet: Option<String>,
     pub wallets: Option<HashMap<String, Option<String>>>,
+    /// Key used to sign proofs in dev mode, defaults to the test key
+    pub dev_key: Option<SecretKey>,
+    /// Beacon release to deploy, defaults to the latest release
+    pub beacon_version: Option<String>,
+    /// SHA-256 checksum the downloaded WASM of `beacon_version` must match
+    pub beacon_checksum: Option<String>,
+    /// Contracts deployed by `entropy deploy`, by name
+    pub contracts: Option<HashMap<String, ContractConfig>>,
 }
 
 impl ProjectConfig {
@@ -33,6 +42,10 @@ impl ProjectConfig {
             default_network: Some(network_name),
             default_wallet: None,
             wallets: None,
+            dev_key: None,
+            beacon_version: None,
+            beacon_checksum: None,
+            contracts: None,
         };
 
         if Confirm::with_theme(&theme)
@@ -98,6 +111,115 @@ impl ProjectConfig {
             self.networks.as_mut().and_then(|n| n.get_mut(name)),
         ))
     }
+
+    /// The checksum a download of beacon release `version` must match. The pinned
+    /// checksum belongs to `beacon_version`, so other releases are not pinned.
+    pub fn pinned_checksum(&self, version: Option<&str>) -> Option<&str> {
+        self.beacon_checksum
+            .as_deref()
+            .filter(|_| version.is_some() && version == self.beacon_version.as_deref())
+    }
+}
+
+/// Loads a project config, along with the network to use, exiting if either is
+/// missing.
+pub fn load_network(config_path: &str, network: Option<&String>) -> (ProjectConfig, Network) {
+    let theme = CLITheme::default();
+    let config = ConfigUtils::load(&config_path).unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Error loading config file: "),
+            theme.error.apply_to(e.to_string())
+        );
+        std::process::exit(1);
+    });
+    let ConfigType::Project(config) = config else {
+        println!(
+            "{}",
+            theme.error.apply_to("Config file is not a project config")
+        );
+        std::process::exit(1);
+    };
+    let network = match config.get_network(&network.cloned()) {
+        Ok((_, Some(network))) => network,
+        Ok((name, None)) => {
+            println!(
+                "{} {} {}",
+                theme.error.apply_to("Network"),
+                theme.highlight.apply_to(name),
+                theme.error.apply_to("not found in config file.")
+            );
+            std::process::exit(1);
+        }
+        Err(()) => {
+            println!(
+                "{}",
+                theme.error.apply_to("No network specified. Please specify a network with the --network flag or set a default network in the config file.")
+            );
+            std::process::exit(1);
+        }
+    };
+    (config, network)
+}
+
+/// Loads a project config, along with the network and wallet to use, exiting
+/// if any of them are missing.
+pub fn load_project(
+    config_path: &str,
+    network: Option<&String>,
+    wallet: Option<String>,
+) -> (ProjectConfig, Network, Wallet) {
+    let theme = CLITheme::default();
+    let (config, network) = load_network(config_path, network);
+
+    let wallet_name = wallet.or_else(|| config.default_wallet.clone()).unwrap_or_else(|| {
+            println!(
+                "{}",
+                theme.error.apply_to("No wallet specified. Please specify a wallet with the --wallet flag or set a default wallet in the config file.")
+            );
+            std::process::exit(1);
+        });
+
+    let wallet = config
+        .wallets
+        .as_ref()
+        .and_then(|wallets| wallets.get(&wallet_name));
+
+    let wallet = match wallet {
+        Some(Some(mnemonic)) => Wallet::new(mnemonic.clone(), network.clone()),
+        Some(None) => {
+            let mnemonic = std::env::var("MNEMONIC").unwrap_or_else(|_| {
+                println!(
+                    "{} {} {}",
+                    theme.error.apply_to("Mnemonic for wallet"),
+                    theme.highlight.apply_to(&wallet_name),
+                    theme
+                        .error
+                        .apply_to("not found in config file or MNEMONIC environment variable.")
+                );
+                std::process::exit(1);
+            });
+            Wallet::new(mnemonic, network.clone())
+        }
+        None => {
+            println!(
+                "{} {} {}",
+                theme.error.apply_to("Wallet"),
+                theme.highlight.apply_to(&wallet_name),
+                theme.error.apply_to("not found in config file.")
+            );
+            std::process::exit(1);
+        }
+    }
+    .unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Error creating wallet:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+    (config, network, wallet)
 }
 
 impl Config for ProjectConfig {
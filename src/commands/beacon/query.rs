@@ -0,0 +1,393 @@
+use clap::{Parser, Subcommand};
+use ecvrf_rs::PublicKey;
+use entropy_beacon_cosmos::{
+    beacon::CalculateFeeResponse,
+    provide::{ActiveRequestInfo, BeaconConfigResponse},
+};
+
+use crate::{
+    cosmos::network::Network,
+    utils::{
+        beacon_interface::{
+            calculate_fee, fetch_active_requests, fetch_config, fetch_key_status,
+            fetch_last_entropy,
+        },
+        CLITheme,
+    },
+};
+
+use super::project_config::load_network;
+
+#[derive(Debug, Parser, Clone)]
+pub struct QueryCommandOptions {
+    /// Optional path to the configuration file
+    #[clap(short, long)]
+    #[clap(default_value = "entropy.json")]
+    pub config: String,
+    /// Network to use (defined in config). Optional if default network is set in config
+    #[clap(short, long)]
+    pub network: Option<String>,
+    /// Address of the beacon to query. Defaults to the beacon deployed on the network
+    #[clap(short, long)]
+    pub address: Option<String>,
+    /// Print results as JSON
+    #[clap(long, global = true)]
+    pub json: bool,
+    #[clap(subcommand)]
+    pub command: QueryCommand,
+}
+
+#[derive(Debug, Subcommand, Clone)]
+pub enum QueryCommand {
+    #[clap(about = "Show the last entropy submitted to the beacon")]
+    LastEntropy,
+    #[clap(about = "List active requests")]
+    Requests {
+        /// Only show requests made by this address
+        #[clap(short, long)]
+        submitter: Option<String>,
+        /// Only show requests calling back this address
+        #[clap(long)]
+        callback_address: Option<String>,
+        /// Only show requests made at or after this block height
+        #[clap(long)]
+        since_height: Option<u64>,
+        /// Maximum number of (oldest) requests to show
+        #[clap(short, long)]
+        limit: Option<usize>,
+    },
+    #[clap(about = "Show the beacon configuration and fees")]
+    Config {
+        /// Callback gas to calculate the request fee for
+        #[clap(short, long)]
+        #[clap(default_value = "200000")]
+        gas_limit: u64,
+    },
+    #[clap(about = "Show the whitelist status of public keys")]
+    Keys {
+        /// Public keys to look up. The beacon does not list whitelisted keys, so keys must be given
+        #[clap(required = true)]
+        public_keys: Vec<String>,
+    },
+    #[clap(about = "Send a raw smart query to the beacon")]
+    Raw {
+        /// Query message, as JSON
+        query: String,
+    },
+}
+
+#[allow(clippy::too_many_lines)]
+pub async fn query_cmd(options: QueryCommandOptions) {
+    let theme = CLITheme::default();
+    let (_, network) = load_network(&options.config, options.network.as_ref());
+    let address = options
+        .address
+        .clone()
+        .or_else(|| network.deployed_beacon_address.clone())
+        .unwrap_or_else(|| {
+            println!(
+                "{}",
+                theme.error.apply_to(
+                    "No deployed beacon found in config file, please deploy a beacon or pass --address."
+                )
+            );
+            std::process::exit(1);
+        });
+
+    match options.command {
+        QueryCommand::LastEntropy => {
+            let res = fetch_last_entropy(&network, &address)
+                .await
+                .unwrap_or_else(|e| {
+                    println!(
+                        "{} {}",
+                        theme.error.apply_to("Error fetching last entropy:"),
+                        theme.highlight.apply_to(e)
+                    );
+                    std::process::exit(1);
+                });
+            if options.json {
+                print_json(&res);
+            } else {
+                println!("{}", res.entropy);
+            }
+        }
+        QueryCommand::Requests {
+            submitter,
+            callback_address,
+            since_height,
+            limit,
+        } => {
+            let requests = fetch_active_requests(&network, &address)
+                .await
+                .unwrap_or_else(|e| {
+                    println!(
+                        "{} {}",
+                        theme.error.apply_to("Error fetching active requests:"),
+                        theme.highlight.apply_to(e)
+                    );
+                    std::process::exit(1);
+                })
+                .requests;
+            let requests = filter_requests(
+                requests,
+                submitter.as_deref(),
+                callback_address.as_deref(),
+                since_height,
+                limit,
+            );
+            if options.json {
+                print_json(&requests);
+            } else {
+                print_requests(&requests);
+            }
+        }
+        QueryCommand::Config { gas_limit } => {
+            let config = fetch_config(&network, &address).await.unwrap_or_else(|e| {
+                println!(
+                    "{} {}",
+                    theme.error.apply_to("Error fetching beacon config:"),
+                    theme.highlight.apply_to(e)
+                );
+                std::process::exit(1);
+            });
+            let fee = calculate_fee(&network, &address, gas_limit)
+                .await
+                .unwrap_or_else(|e| {
+                    println!(
+                        "{} {}",
+                        theme.error.apply_to("Error fetching beacon fee:"),
+                        theme.highlight.apply_to(e)
+                    );
+                    std::process::exit(1);
+                });
+            if options.json {
+                print_json(&serde_json::json!({ "config": config, "fee": fee }));
+            } else {
+                print_config(&config, &fee, gas_limit);
+            }
+        }
+        QueryCommand::Keys { public_keys } => {
+            query_keys(&network, &address, public_keys, options.json).await;
+        }
+        QueryCommand::Raw { query } => {
+            let query = serde_json::from_str::<serde_json::Value>(&query).unwrap_or_else(|e| {
+                println!(
+                    "{} {}",
+                    theme.error.apply_to("Query is not valid JSON:"),
+                    theme.highlight.apply_to(e)
+                );
+                std::process::exit(1);
+            });
+            let res = network.query(address, query).await.unwrap_or_else(|e| {
+                println!(
+                    "{} {}",
+                    theme.error.apply_to("Error querying beacon:"),
+                    theme.highlight.apply_to(e)
+                );
+                std::process::exit(1);
+            });
+            print_json(&res);
+        }
+    }
+}
+
+async fn query_keys(network: &Network, address: &str, public_keys: Vec<String>, json: bool) {
+    let theme = CLITheme::default();
+    let mut statuses = vec![];
+    for public_key in public_keys {
+        let bytes = hex::decode(&public_key)
+            .ok()
+            .filter(|b| b.len() == 32)
+            .unwrap_or_else(|| {
+                println!(
+                    "{} {}",
+                    theme.error.apply_to("Invalid public key:"),
+                    theme.highlight.apply_to(&public_key)
+                );
+                std::process::exit(1);
+            });
+        let key = PublicKey::from_bytes(&bytes);
+        let status = fetch_key_status(network, address, key)
+            .await
+            .unwrap_or_else(|e| {
+                println!(
+                    "{} {}",
+                    theme.error.apply_to("Error fetching key status:"),
+                    theme.highlight.apply_to(e)
+                );
+                std::process::exit(1);
+            });
+        statuses.push((public_key, status));
+    }
+    if json {
+        let statuses = statuses
+            .into_iter()
+            .map(|(public_key, status)| {
+                serde_json::json!({ "public_key": public_key, "status": status })
+            })
+            .collect::<Vec<_>>();
+        print_json(&statuses);
+    } else {
+        for (public_key, status) in statuses {
+            let state = match (status.whitelisted, status.active) {
+                (false, _) => theme.error.apply_to("not whitelisted".to_string()),
+                (true, true) => theme
+                    .success
+                    .apply_to(format!("active since block {}", status.activation_height)),
+                (true, false) => theme.warning.apply_to(format!(
+                    "whitelisted, active at block {}",
+                    status.activation_height
+                )),
+            };
+            println!("{public_key} {state}");
+        }
+    }
+}
+
+fn print_json(value: &impl serde::Serialize) {
+    println!("{}", serde_json::to_string_pretty(value).unwrap());
+}
+
+fn filter_requests(
+    requests: Vec<ActiveRequestInfo>,
+    submitter: Option<&str>,
+    callback_address: Option<&str>,
+    since_height: Option<u64>,
+    limit: Option<usize>,
+) -> Vec<ActiveRequestInfo> {
+    requests
+        .into_iter()
+        .filter(|r| submitter.is_none_or(|s| r.submitter == s))
+        .filter(|r| callback_address.is_none_or(|a| r.callback_address == a))
+        .filter(|r| since_height.is_none_or(|h| r.submitted_block_height >= h))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+fn print_requests(requests: &[ActiveRequestInfo]) {
+    let theme = CLITheme::default();
+    if requests.is_empty() {
+        println!("{}", theme.dimmed.apply_to("No active requests."));
+        return;
+    }
+    println!(
+        "{}",
+        theme.dimmed.apply_to(format!(
+            "{:<8} {:<46} {:<46} {:>10} {:>12} {:>10}",
+            "ID", "Submitter", "Callback", "Gas limit", "Bounty", "Height"
+        ))
+    );
+    for r in requests {
+        println!(
+            "{:<8} {:<46} {:<46} {:>10} {:>12} {:>10}",
+            r.id.to_string(),
+            r.submitter.as_str(),
+            r.callback_address.as_str(),
+            r.callback_gas_limit,
+            r.submitted_bounty_amount.to_string(),
+            r.submitted_block_height
+        );
+    }
+}
+
+fn print_config(config: &BeaconConfigResponse, fee: &CalculateFeeResponse, gas_limit: u64) {
+    let theme = CLITheme::default();
+    let denom = &config.native_denom;
+    let rows = [
+        ("Native denom", denom.clone()),
+        ("Paused", config.paused.to_string()),
+        ("Permissioned", config.permissioned.to_string()),
+        ("Test mode", config.test_mode.to_string()),
+        (
+            "Subsidize callbacks",
+            config.subsidize_callbacks.to_string(),
+        ),
+        (
+            "Whitelist deposit",
+            format!("{}{denom}", config.whitelist_deposit_amt),
+        ),
+        (
+            "Refund increment",
+            format!("{}{denom}", config.refund_increment_amt),
+        ),
+        (
+            "Key activation delay",
+            format!("{} blocks", config.key_activation_delay),
+        ),
+        ("Protocol fee", format!("{}{denom}", config.protocol_fee)),
+        ("Submitter share", config.submitter_share.to_string()),
+        ("Gas price", fee.gas_price.to_string()),
+        (
+            "Request fee",
+            format!("{}{denom} for {gas_limit} callback gas", fee.fee),
+        ),
+    ];
+    for (name, value) in rows {
+        println!("{} {}", theme.dimmed.apply_to(format!("{name}:")), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{Addr, Uint128};
+
+    use super::*;
+
+    fn request(id: u128, submitter: &str, height: u64) -> ActiveRequestInfo {
+        ActiveRequestInfo {
+            id: Uint128::new(id),
+            callback_gas_limit: 100_000,
+            callback_address: Addr::unchecked("callback"),
+            submitter: Addr::unchecked(submitter),
+            submitted_block_height: height,
+            submitted_bounty_amount: Uint128::zero(),
+        }
+    }
+
+    #[test]
+    fn requests_are_filtered() {
+        let requests = vec![
+            request(1, "alice", 5),
+            request(2, "bob", 6),
+            request(3, "alice", 7),
+        ];
+        let ids = |requests: Vec<ActiveRequestInfo>| {
+            requests.iter().map(|r| r.id.u128()).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(filter_requests(
+                requests.clone(),
+                Some("alice"),
+                None,
+                None,
+                None
+            )),
+            vec![1, 3]
+        );
+        assert_eq!(
+            ids(filter_requests(
+                requests.clone(),
+                None,
+                Some("callback"),
+                Some(6),
+                None
+            )),
+            vec![2, 3]
+        );
+        assert_eq!(
+            ids(filter_requests(requests, None, Some("other"), None, None)),
+            Vec::<u128>::new()
+        );
+        assert_eq!(
+            ids(filter_requests(
+                vec![request(1, "alice", 5), request(2, "bob", 6)],
+                None,
+                None,
+                None,
+                Some(1)
+            )),
+            vec![1]
+        );
+    }
+}
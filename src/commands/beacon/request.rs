@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use clap::Parser;
+use cosmrs::AccountId;
+use cosmwasm_std::{Addr, Binary};
+use entropy_beacon_cosmos::beacon::RequestEntropyMsg;
+
+use crate::utils::{beacon_interface::Beacon, CLITheme};
+
+use super::project_config::load_project;
+
+#[derive(Debug, Parser, Clone)]
+pub struct RequestCommandOptions {
+    /// Optional path to the configuration file
+    #[clap(short, long)]
+    #[clap(default_value = "entropy.json")]
+    pub config: String,
+    /// Network to use (defined in config). Optional if default network is set in config
+    #[clap(short, long)]
+    pub network: Option<String>,
+    /// Wallet to use (defined in config). Optional if default wallet is set in config
+    #[clap(short, long)]
+    pub wallet: Option<String>,
+    /// Address of the contract to call back with the entropy
+    #[clap(short = 'a', long)]
+    pub callback_address: String,
+    /// Gas provisioned for the callback
+    #[clap(short, long)]
+    #[clap(default_value = "200000")]
+    pub gas_limit: u64,
+    /// Message included in the callback, as JSON
+    #[clap(short, long)]
+    #[clap(default_value = "{}")]
+    pub msg: String,
+    /// Fee to send with the request, in the native denom of the beacon. Calculated
+    /// by the beacon if not set
+    #[clap(short, long)]
+    pub fee: Option<u64>,
+}
+
+pub async fn request_cmd(options: RequestCommandOptions) {
+    let theme = CLITheme::default();
+
+    if AccountId::from_str(&options.callback_address).is_err() {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Invalid callback address:"),
+            theme.highlight.apply_to(&options.callback_address)
+        );
+        std::process::exit(1);
+    }
+    let callback_msg =
+        serde_json::from_str::<serde_json::Value>(&options.msg).unwrap_or_else(|e| {
+            println!(
+                "{} {}",
+                theme.error.apply_to("Callback message is not valid JSON:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+
+    let (_, network, wallet) =
+        load_project(&options.config, options.network.as_ref(), options.wallet);
+    let beacon_address = network.deployed_beacon_address.clone().unwrap_or_else(|| {
+        println!(
+            "{}",
+            theme
+                .error
+                .apply_to("No deployed beacon found in config file, please deploy a beacon first.")
+        );
+        std::process::exit(1);
+    });
+    let beacon = Beacon::new(network, wallet, beacon_address);
+
+    let config = beacon.fetch_config().await.unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Error fetching beacon config:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+    if config.paused {
+        println!(
+            "{}",
+            theme
+                .warning
+                .apply_to("The beacon is paused, the request will likely be rejected.")
+        );
+    }
+
+    let fee = match options.fee {
+        Some(fee) => fee,
+        None => calculate_fee(&beacon, options.gas_limit, &config.native_denom).await,
+    };
+
+    let request = RequestEntropyMsg {
+        callback_gas_limit: options.gas_limit,
+        callback_address: Addr::unchecked(options.callback_address),
+        callback_msg: Binary::from(callback_msg.to_string().into_bytes()),
+    };
+    match beacon
+        .request_entropy(request, fee, &config.native_denom)
+        .await
+    {
+        Ok(res) => {
+            println!(
+                "{} {}",
+                theme.success.apply_to("Entropy requested successfully!"),
+                theme.highlight.apply_to(&res.txhash)
+            );
+            let request_id = res
+                .logs
+                .iter()
+                .flat_map(|log| &log.events)
+                .find_map(|event| event.attributes.get("request_id"));
+            if let Some(request_id) = request_id {
+                println!(
+                    "{} {}",
+                    theme.dimmed.apply_to("Request id:"),
+                    theme.highlight.apply_to(request_id)
+                );
+            }
+        }
+        Err(e) => {
+            println!(
+                "{} {}",
+                theme.error.apply_to("Error requesting entropy:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn calculate_fee(beacon: &Beacon, gas_limit: u64, denom: &str) -> u64 {
+    let theme = CLITheme::default();
+    let fee = beacon.calculate_fee(gas_limit).await.unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Error calculating fee:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+    println!(
+        "{} {}{} {}",
+        theme.dimmed.apply_to("Fee:"),
+        fee.fee,
+        denom,
+        theme
+            .dimmed
+            .apply_to(format!("(gas price {})", fee.gas_price))
+    );
+    fee.fee
+}
@@ -0,0 +1,363 @@
+use std::{str::FromStr, time::Duration};
+
+use clap::Parser;
+use cosmrs::{cosmwasm::MsgMigrateContract, AccountId};
+use indicatif::ProgressBar;
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+    cosmos::queries::{ContractInfo, ContractVersion, QueryError},
+    utils::{
+        config::ConfigUtils,
+        deploy::{beacon_wasm, known_release, store_code},
+        CLITheme,
+    },
+};
+
+use super::project_config::load_project;
+
+#[derive(Debug, Parser, Clone)]
+pub struct UpgradeCommandOptions {
+    /// Optional path to the configuration file
+    #[clap(short, long)]
+    #[clap(default_value = "entropy.json")]
+    pub config: String,
+    /// Network to use (defined in config). Optional if default network is set in config
+    #[clap(short, long)]
+    pub network: Option<String>,
+    /// Wallet to use (defined in config), must be the admin of the beacon. Optional if default wallet is set in config
+    #[clap(short, long)]
+    pub wallet: Option<String>,
+    /// Optional path to the wasm file to migrate to, if not provided will download from github
+    #[clap(long)]
+    pub wasm: Option<String>,
+    /// Code id of an already uploaded beacon contract to migrate to, skipping the upload
+    #[clap(long, conflicts_with = "wasm")]
+    pub code_id: Option<u64>,
+    /// Release of the beacon to download, e.g. `v1.0.0`. Defaults to `beacon_version` in the config file, or the latest release
+    #[clap(long, conflicts_with_all = &["wasm", "code_id"])]
+    pub beacon_version: Option<String>,
+    /// Migrate message, as JSON
+    #[clap(short, long)]
+    #[clap(default_value = "{}")]
+    pub msg: String,
+    /// Migrate without asking for confirmation
+    #[clap(short, long)]
+    pub yes: bool,
+    /// Allow migrating to an older release than the one the beacon is running
+    #[clap(long)]
+    pub allow_downgrade: bool,
+}
+
+#[allow(clippy::too_many_lines)]
+pub async fn upgrade_cmd(options: UpgradeCommandOptions) {
+    let theme = CLITheme::default();
+    let migrate_msg = serde_json::from_str::<serde_json::Value>(&options.msg).unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Migrate message is not valid JSON:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+
+    let (mut config, network, wallet) =
+        load_project(&options.config, options.network.as_ref(), options.wallet);
+    let beacon_address = network.deployed_beacon_address.clone().unwrap_or_else(|| {
+        println!(
+            "{}",
+            theme
+                .error
+                .apply_to("No deployed beacon found in config file, please deploy a beacon first.")
+        );
+        std::process::exit(1);
+    });
+
+    let info = network
+        .fetch_contract_info(&beacon_address)
+        .await
+        .unwrap_or_else(|e| {
+            println!(
+                "{} {}",
+                theme.error.apply_to("Error fetching beacon contract info:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+    let version = network
+        .fetch_contract_version(&beacon_address)
+        .await
+        .unwrap_or_else(|e| {
+            println!(
+                "{} {}",
+                theme
+                    .error
+                    .apply_to("Error fetching beacon contract version:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+    if let Err(e) = check_admin(&info, wallet.address.as_ref()) {
+        println!("{}", theme.error.apply_to(e));
+        std::process::exit(1);
+    }
+    println!(
+        "{} {} {}",
+        theme.dimmed.apply_to("Current beacon:"),
+        theme
+            .highlight
+            .apply_to(format!("code id {}", info.code_id)),
+        match &version {
+            Some(version) => format!("({} {})", version.contract, version.version),
+            None => "(no contract version)".to_string(),
+        }
+    );
+
+    // Nothing is uploaded until the migration is confirmed, so the target is checked first
+    if options.code_id == Some(info.code_id) {
+        already_running(info.code_id);
+    }
+    let pb = ProgressBar::new(1);
+    pb.enable_steady_tick(Duration::from_millis(80));
+    pb.set_style(CLITheme::spinner());
+    pb.set_message("Fetching current beacon code...");
+    let current_checksum = network
+        .fetch_code_info(info.code_id)
+        .await
+        .unwrap_or_else(|e| code_error(&pb, info.code_id, &e))
+        .checksum;
+    let beacon_version = options
+        .beacon_version
+        .or_else(|| config.beacon_version.clone());
+    let (target_wasm, target_version) = if let Some(code_id) = options.code_id {
+        pb.set_message(format!("Fetching code {code_id}..."));
+        // The WASM itself is needed to check the contract name
+        let code = network
+            .fetch_code(code_id)
+            .await
+            .unwrap_or_else(|e| code_error(&pb, code_id, &e));
+        let version = known_release(&code.info.checksum);
+        (code.wasm, version)
+    } else {
+        let pinned_checksum = config.pinned_checksum(beacon_version.as_deref());
+        beacon_wasm(
+            &pb,
+            options.wasm,
+            beacon_version.as_deref(),
+            pinned_checksum,
+        )
+        .await
+    };
+    pb.finish_and_clear();
+    if hex::encode(Sha256::digest(&target_wasm)) == current_checksum {
+        already_running(info.code_id);
+    }
+    if let Err(e) = check_target(
+        version.as_ref(),
+        target_version.as_deref(),
+        &target_wasm,
+        options.allow_downgrade,
+    ) {
+        println!("{}", theme.error.apply_to(e));
+        std::process::exit(1);
+    }
+
+    let target = match (options.code_id, &target_version) {
+        (Some(code_id), _) => format!("code id {code_id}"),
+        (None, Some(version)) => format!("release {version}"),
+        (None, None) => "the new WASM".to_string(),
+    };
+    if !options.yes
+        && !dialoguer::Confirm::with_theme(&theme)
+            .with_prompt(format!(
+                "Migrate beacon from code id {} to {target}?",
+                info.code_id
+            ))
+            .default(false)
+            .interact()
+            .unwrap()
+    {
+        return;
+    }
+
+    let code_id = if let Some(code_id) = options.code_id {
+        code_id
+    } else {
+        let pb = ProgressBar::new(1);
+        pb.enable_steady_tick(Duration::from_millis(80));
+        pb.set_style(CLITheme::spinner());
+        let code_id = store_code(&pb, &network, &wallet, target_wasm, "beacon").await;
+        pb.finish_and_clear();
+        code_id
+    };
+
+    let pb = ProgressBar::new(1);
+    pb.enable_steady_tick(Duration::from_millis(80));
+    pb.set_style(CLITheme::spinner());
+    pb.set_message("Migrating beacon contract...");
+    let msg = MsgMigrateContract {
+        sender: wallet.address.clone(),
+        contract: AccountId::from_str(&beacon_address).unwrap_or_else(|e| {
+            pb.set_style(CLITheme::failed_spinner());
+            pb.set_prefix("✗");
+            pb.finish_with_message(format!("{} {}", "Invalid beacon address:", e));
+            std::process::exit(1);
+        }),
+        code_id,
+        msg: migrate_msg.to_string().into_bytes(),
+    };
+    let hash = wallet
+        .broadcast_msg(msg, None, None)
+        .await
+        .unwrap_or_else(|err| {
+            pb.set_style(CLITheme::failed_spinner());
+            pb.set_prefix("✗");
+            pb.finish_with_message(format!("{} {}", "Error migrating beacon contract:", err));
+            std::process::exit(1);
+        });
+    pb.set_message("Waiting for transaction to be included in block...");
+    wallet.wait_for_hash(hash).await.unwrap_or_else(|err| {
+        pb.set_style(CLITheme::failed_spinner());
+        pb.set_prefix("✗");
+        pb.finish_with_message(format!(
+            "{} {}",
+            "Error waiting for transaction to be included in block:", err
+        ));
+        std::process::exit(1);
+    });
+
+    let version = network
+        .fetch_contract_version(&beacon_address)
+        .await
+        .ok()
+        .flatten()
+        .map(|version| format!(" ({} {})", version.contract, version.version))
+        .unwrap_or_default();
+    pb.set_style(CLITheme::success_spinner());
+    pb.set_prefix("✓");
+    pb.finish_with_message(format!(
+        "{} {}{}",
+        "Beacon contract migrated to",
+        theme.highlight.apply_to(format!("code id {code_id}")),
+        version
+    ));
+
+    // Without a known release, the recorded one is left as is rather than forgotten
+    if let (Ok((_, Some(network))), Some(version)) =
+        (config.get_network_mut(&options.network), target_version)
+    {
+        network.deployed_beacon_version = Some(version);
+    }
+    ConfigUtils::save(&config, &options.config).unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            theme.error.apply_to("Error updating config file: "),
+            theme.error.apply_to(e.to_string())
+        );
+        std::process::exit(1);
+    });
+}
+
+fn already_running(code_id: u64) -> ! {
+    let theme = CLITheme::default();
+    println!(
+        "{} {}",
+        theme
+            .error
+            .apply_to("The beacon is already running code id"),
+        theme.highlight.apply_to(code_id)
+    );
+    std::process::exit(1);
+}
+
+fn code_error(pb: &ProgressBar, code_id: u64, e: &QueryError) -> ! {
+    pb.set_style(CLITheme::failed_spinner());
+    pb.set_prefix("✗");
+    pb.finish_with_message(format!("Error fetching code {code_id}: {e}"));
+    std::process::exit(1);
+}
+
+/// Parses a version such as `v1.2.3` or `1.2.3-beta` into its numeric components.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// Checks that the target of a migration is the same contract as the running one,
+/// and not an older release of it, as far as can be told before migrating.
+fn check_target(
+    current: Option<&ContractVersion>,
+    target_version: Option<&str>,
+    target_wasm: &[u8],
+    allow_downgrade: bool,
+) -> Result<(), String> {
+    let Some(current) = current else {
+        return Ok(());
+    };
+    // cw2 stores the contract name as a constant, which ends up in the WASM as is
+    let name = current.contract.as_bytes();
+    if !target_wasm.windows(name.len()).any(|window| window == name) {
+        return Err(format!(
+            "The new WASM is not a {} contract, refusing to migrate the beacon to it.",
+            current.contract
+        ));
+    }
+    if let (Some(current_version), Some(target)) = (parse_version(&current.version), target_version)
+    {
+        let older = parse_version(target).is_some_and(|version| version < current_version);
+        if older && !allow_downgrade {
+            return Err(format!(
+                "Release {target} is older than the running version {}, pass --allow-downgrade to migrate anyway.",
+                current.version
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `sender` is allowed to migrate the contract.
+fn check_admin(info: &ContractInfo, sender: &str) -> Result<(), String> {
+    match &info.admin {
+        None => Err("The beacon has no admin, and cannot be migrated.".to_string()),
+        Some(admin) if admin != sender => Err(format!(
+            "The beacon can only be migrated by its admin, {admin}."
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_admin_can_migrate() {
+        let info = |admin: Option<&str>| ContractInfo {
+            code_id: 1,
+            admin: admin.map(ToString::to_string),
+            label: "Entropy Beacon".to_string(),
+        };
+        assert!(check_admin(&info(Some("terra1admin")), "terra1admin").is_ok());
+        assert!(check_admin(&info(Some("terra1admin")), "terra1other").is_err());
+        assert!(check_admin(&info(None), "terra1admin").is_err());
+    }
+
+    #[test]
+    fn targets_must_be_the_same_contract_and_not_older() {
+        let current = ContractVersion {
+            contract: "crates.io:entropy-beacon".to_string(),
+            version: "1.2.0".to_string(),
+        };
+        let wasm = b"\0asm...crates.io:entropy-beacon...";
+        assert!(check_target(Some(&current), Some("v1.3.0"), wasm, false).is_ok());
+        assert!(check_target(Some(&current), None, wasm, false).is_ok());
+        assert!(check_target(Some(&current), Some("v1.1.5"), wasm, false).is_err());
+        assert!(check_target(Some(&current), Some("v1.1.5"), wasm, true).is_ok());
+        assert!(check_target(Some(&current), Some("v1.3.0"), b"\0asm...other", false).is_err());
+        assert!(check_target(None, Some("v0.1.0"), b"\0asm", false).is_ok());
+        assert_eq!(parse_version("v1.2.3-beta"), Some(vec![1, 2, 3]));
+        assert_eq!(parse_version("latest"), None);
+    }
+}
@@ -0,0 +1,299 @@
+use clap::Parser;
+use ecvrf_rs::{encode_hex, Proof, PublicKey};
+
+use crate::utils::{vrf::verify_proof, CLITheme};
+
+use super::project_config::load_network;
+
+#[derive(Debug, Parser, Clone)]
+pub struct VerifyCommandOptions {
+    /// Hash of a transaction submitting entropy to the beacon
+    #[clap(short, long, conflicts_with = "json")]
+    pub tx: Option<String>,
+    /// Proof to verify, as JSON or a path to a JSON file (`-` for standard input). Accepts a
+    /// proof, a `submit_entropy` message, or its contents
+    #[clap(short, long, required_unless_present = "tx")]
+    pub json: Option<String>,
+    /// Public key the proof must be signed with
+    #[clap(long)]
+    pub public_key: Option<String>,
+    /// Entropy the proof must be chained from, in hex
+    #[clap(long)]
+    pub previous_entropy: Option<String>,
+    /// Entropy the proof must produce, in hex
+    #[clap(long)]
+    pub entropy: Option<String>,
+    /// Optional path to the configuration file, used to look up transactions
+    #[clap(short, long)]
+    #[clap(default_value = "entropy.json")]
+    pub config: String,
+    /// Network to use (defined in config). Optional if default network is set in config
+    #[clap(short, long)]
+    pub network: Option<String>,
+}
+
+pub async fn verify_cmd(options: VerifyCommandOptions) {
+    let theme = CLITheme::default();
+
+    let (proofs, tx_entropies) = if let Some(tx_hash) = &options.tx {
+        let (_, network) = load_network(&options.config, options.network.as_ref());
+        let tx = network.fetch_tx(tx_hash).await.unwrap_or_else(|e| {
+            eprintln!(
+                "{} {}",
+                theme.error.apply_to("Error fetching transaction:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+        (proofs_from_tx(&tx), Some(beacon_entropies(&tx)))
+    } else {
+        let input = options.json.as_deref().unwrap_or_default();
+        let json = if input == "-" {
+            std::io::read_to_string(std::io::stdin()).ok()
+        } else if std::path::Path::new(input).is_file() {
+            std::fs::read_to_string(input).ok()
+        } else {
+            Some(input.to_string())
+        }
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .unwrap_or_else(|| {
+            eprintln!("{}", theme.error.apply_to("Input is not valid JSON."));
+            std::process::exit(1);
+        });
+        (proof_from_json(&json).into_iter().collect(), None)
+    };
+
+    if proofs.is_empty() {
+        eprintln!("{}", theme.error.apply_to("No entropy proof found."));
+        std::process::exit(1);
+    }
+
+    let public_key = options.public_key.as_ref().map(|pk| {
+        let bytes = hex::decode(pk)
+            .ok()
+            .filter(|b| b.len() == 32)
+            .unwrap_or_else(|| {
+                eprintln!(
+                    "{} {}",
+                    theme.error.apply_to("Invalid public key:"),
+                    theme.highlight.apply_to(pk)
+                );
+                std::process::exit(1);
+            });
+        PublicKey::from_bytes(&bytes)
+    });
+    let previous_entropy = options
+        .previous_entropy
+        .as_ref()
+        .map(|e| decode_entropy(e, "previous entropy"));
+    let expected_entropy = options
+        .entropy
+        .as_ref()
+        .map(|e| decode_entropy(e, "entropy"));
+
+    let mut all_valid = true;
+    for (index, proof) in proofs.iter().enumerate() {
+        println!("{} {}", theme.dimmed.apply_to("Signer:"), proof.signer);
+        println!(
+            "{} {}",
+            theme.dimmed.apply_to("Previous entropy:"),
+            encode_hex(&proof.message_bytes)
+        );
+        match verify_proof(proof, public_key.as_ref(), previous_entropy.as_deref()) {
+            Ok(entropy) => {
+                let entropy = encode_hex(&entropy);
+                println!("{} {}", theme.dimmed.apply_to("Entropy:"), entropy);
+                if expected_entropy
+                    .as_ref()
+                    .is_some_and(|expected| encode_hex(expected) != entropy)
+                {
+                    all_valid = false;
+                    println!(
+                        "{}\n",
+                        theme
+                            .error
+                            .apply_to("Proof is valid, but does not produce the expected entropy.")
+                    );
+                    continue;
+                }
+                // Submissions are executed in order, each emitting the entropy it produced
+                if tx_entropies
+                    .as_ref()
+                    .is_some_and(|e| !matches_tx_entropy(&entropy, e.get(index)))
+                {
+                    all_valid = false;
+                    continue;
+                }
+                println!("{}\n", theme.success.apply_to("Proof is valid."));
+            }
+            Err(e) => {
+                all_valid = false;
+                println!("{} {}\n", theme.error.apply_to("Proof is invalid:"), e);
+            }
+        }
+    }
+
+    if !all_valid {
+        std::process::exit(1);
+    }
+}
+
+/// Compares entropy with the entropy the beacon emitted in the verified transaction.
+fn matches_tx_entropy(entropy: &str, tx_entropy: Option<&String>) -> bool {
+    let theme = CLITheme::default();
+    match tx_entropy {
+        Some(tx_entropy) if tx_entropy == entropy => println!(
+            "{}",
+            theme
+                .dimmed
+                .apply_to("Entropy matches the entropy emitted by the beacon.")
+        ),
+        Some(tx_entropy) => {
+            println!(
+                "{} {}\n",
+                theme
+                    .error
+                    .apply_to("Proof is valid, but the beacon emitted different entropy:"),
+                theme.highlight.apply_to(tx_entropy)
+            );
+            return false;
+        }
+        None => println!(
+            "{}",
+            theme
+                .dimmed
+                .apply_to("The beacon emitted no entropy to compare against.")
+        ),
+    }
+    true
+}
+
+fn decode_entropy(entropy: &str, name: &str) -> Vec<u8> {
+    let theme = CLITheme::default();
+    hex::decode(entropy).unwrap_or_else(|_| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to(format!("Invalid {name}:")),
+            theme.highlight.apply_to(entropy)
+        );
+        std::process::exit(1);
+    })
+}
+
+/// Extracts a proof from a proof, a `submit_entropy` message, or its contents.
+fn proof_from_json(json: &serde_json::Value) -> Option<Proof> {
+    let json = json.get("submit_entropy").unwrap_or(json);
+    let json = match json.get("proof") {
+        Some(proof) if proof.is_object() => proof,
+        _ => json,
+    };
+    serde_json::from_value(json.clone()).ok()
+}
+
+/// Extracts the proofs of every `submit_entropy` message in a transaction.
+fn proofs_from_tx(tx: &serde_json::Value) -> Vec<Proof> {
+    tx["tx"]["body"]["messages"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|msg| match &msg["msg"] {
+            // Older LCDs return contract messages base64 encoded
+            serde_json::Value::String(encoded) => base64::decode(encoded)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+            json => Some(json.clone()),
+        })
+        .filter(|json| json.get("submit_entropy").is_some())
+        .filter_map(|json| proof_from_json(&json))
+        .collect()
+}
+
+/// Extracts the `entropy` attributes emitted by the contracts the `submit_entropy`
+/// messages of a transaction were sent to, in the order they were emitted.
+fn beacon_entropies(tx: &serde_json::Value) -> Vec<String> {
+    let beacons = tx["tx"]["body"]["messages"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|msg| msg["contract"].as_str())
+        .collect::<Vec<_>>();
+    let events = tx["tx_response"]["logs"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|log| log["events"].as_array())
+        .flatten()
+        .filter(|event| {
+            event["type"]
+                .as_str()
+                .is_some_and(|t| t == "wasm" || t.starts_with("wasm-"))
+        });
+
+    let mut entropies = vec![];
+    for event in events {
+        let mut contract = None;
+        for attribute in event["attributes"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+        {
+            match (attribute["key"].as_str(), attribute["value"].as_str()) {
+                (Some("_contract_address"), value) => contract = value,
+                (Some("entropy"), Some(value))
+                    if contract.is_some_and(|c| beacons.contains(&c)) =>
+                {
+                    entropies.push(value.to_lowercase());
+                }
+                _ => {}
+            }
+        }
+    }
+    entropies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::beacon_interface::test_sk;
+
+    #[test]
+    fn proofs_are_extracted_from_messages_and_transactions() {
+        let proof = Proof::new(&test_sk(), [1u8; 64]).unwrap();
+        let msg = serde_json::json!({
+            "submit_entropy": { "proof": proof, "request_ids": ["1"] }
+        });
+        assert_eq!(proof_from_json(&msg), Some(proof.clone()));
+        assert_eq!(
+            proof_from_json(&serde_json::to_value(&proof).unwrap()),
+            Some(proof.clone())
+        );
+
+        let tx = serde_json::json!({
+            "tx": { "body": { "messages": [
+                { "msg": { "whitelist_public_key": {} } },
+                { "msg": base64::encode(msg.to_string()) },
+            ] } }
+        });
+        assert_eq!(proofs_from_tx(&tx), vec![proof]);
+    }
+
+    #[test]
+    fn entropy_is_read_from_beacon_events() {
+        let tx = serde_json::json!({
+            "tx": { "body": { "messages": [{ "contract": "beacon", "msg": {} }] } },
+            "tx_response": { "logs": [{ "events": [{
+                "type": "wasm",
+                "attributes": [
+                    { "key": "_contract_address", "value": "beacon" },
+                    { "key": "entropy", "value": "ABCD" },
+                    { "key": "_contract_address", "value": "consumer" },
+                    { "key": "entropy", "value": "ffff" },
+                ],
+            }] }] },
+        });
+        assert_eq!(beacon_entropies(&tx), vec!["abcd".to_string()]);
+    }
+}
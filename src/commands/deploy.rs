@@ -0,0 +1,125 @@
+use std::{collections::HashMap, time::Duration};
+
+use clap::Parser;
+use indicatif::ProgressBar;
+
+use crate::{
+    commands::beacon::project_config::load_project,
+    utils::{
+        config::ConfigUtils,
+        contracts::{deploy_order, render, BEACON_ADDRESS, WALLET_ADDRESS},
+        deploy::{instantiate_contract, store_code},
+        CLITheme,
+    },
+};
+
+#[derive(Debug, Parser, Clone)]
+pub struct DeployCommandOptions {
+    /// Contracts to deploy, defaults to all contracts in the config file
+    pub contracts: Vec<String>,
+    /// Optional path to the configuration file
+    #[clap(short, long)]
+    #[clap(default_value = "entropy.json")]
+    pub config: String,
+    /// Network to use (defined in config). Optional if default network is set in config
+    #[clap(short, long)]
+    pub network: Option<String>,
+    /// Wallet to use (defined in config). Optional if default wallet is set in config
+    #[clap(short, long)]
+    pub wallet: Option<String>,
+}
+
+pub async fn deploy_cmd(options: DeployCommandOptions) {
+    let theme = CLITheme::default();
+    println!(
+        "{}",
+        dialoguer::console::style(format!("entropy deploy v{}", env!("CARGO_PKG_VERSION"))).bold()
+    );
+
+    let (mut config, network, wallet) =
+        load_project(&options.config, options.network.as_ref(), options.wallet);
+    let contracts = config.contracts.clone().unwrap_or_default();
+    if contracts.is_empty() {
+        println!(
+            "{}",
+            theme.error.apply_to(
+                "No contracts found in config file, add them to the `contracts` section."
+            )
+        );
+        std::process::exit(1);
+    }
+    let order = deploy_order(&contracts, &options.contracts).unwrap_or_else(|e| {
+        println!("{}", theme.error.apply_to(e));
+        std::process::exit(1);
+    });
+
+    let mut values = HashMap::from([(WALLET_ADDRESS.to_string(), wallet.address.to_string())]);
+    if let Some(beacon_address) = &network.deployed_beacon_address {
+        values.insert(BEACON_ADDRESS.to_string(), beacon_address.clone());
+    }
+    for (name, address) in network.deployed_contracts.iter().flatten() {
+        values.insert(format!("{name}_address"), address.clone());
+    }
+
+    for name in order {
+        let contract = &contracts[&name];
+        let pb = ProgressBar::new(1);
+        pb.enable_steady_tick(Duration::from_millis(80));
+        pb.set_style(CLITheme::spinner());
+
+        let msg = render(&contract.instantiate_msg, &values).unwrap_or_else(|e| {
+            pb.set_style(CLITheme::failed_spinner());
+            pb.set_prefix("✗");
+            pb.finish_with_message(format!("Invalid instantiate message for {name}: {e}"));
+            std::process::exit(1);
+        });
+        let wasm_bytes = std::fs::read(&contract.wasm).unwrap_or_else(|err| {
+            pb.set_style(CLITheme::failed_spinner());
+            pb.set_prefix("✗");
+            pb.finish_with_message(format!("Error reading WASM file {}: {err}", contract.wasm));
+            std::process::exit(1);
+        });
+        let code_id = store_code(&pb, &network, &wallet, wasm_bytes, &name).await;
+
+        pb.set_message(format!("Instantiating {name} contract..."));
+        let address = instantiate_contract(
+            &pb,
+            &wallet,
+            code_id,
+            contract.label.clone().unwrap_or_else(|| name.clone()),
+            serde_json::to_vec(&msg).unwrap(),
+            &name,
+        )
+        .await;
+        pb.set_style(CLITheme::success_spinner());
+        pb.set_prefix("✓");
+        pb.finish_with_message(format!(
+            "Contract {name} instantiated at address: {}",
+            theme.highlight.apply_to(&address)
+        ));
+
+        values.insert(format!("{name}_address"), address.clone());
+        // Save after each contract, so a failure doesn't lose the contracts already deployed
+        if let Ok((_, Some(network))) = config.get_network_mut(&options.network) {
+            network
+                .deployed_contracts
+                .get_or_insert_with(HashMap::new)
+                .insert(name, address);
+        }
+        ConfigUtils::save(&config, &options.config).unwrap_or_else(|e| {
+            println!(
+                "{} {}",
+                theme.error.apply_to("Error updating config file: "),
+                theme.error.apply_to(e.to_string())
+            );
+            std::process::exit(1);
+        });
+    }
+
+    println!(
+        "{}",
+        theme
+            .dimmed
+            .apply_to("Updated config file with deployed contract addresses")
+    );
+}
@@ -1,3 +1,4 @@
 pub mod beacon;
+pub mod deploy;
 pub mod worker;
 pub mod network;
\ No newline at end of file
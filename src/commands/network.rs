@@ -89,6 +89,13 @@ fn new_network(config: &String) {
             let network = NetworkConfiguration {
                 network,
                 signer_mnemonic: mnemonic,
+                registered_keys: None,
+                fee_granter: None,
+                label: None,
+                policy: None,
+                max_tx_gas: None,
+                use_block_gas_limit: None,
+                key_rotation: None,
             };
             cfg.networks.insert(name, network);
         }
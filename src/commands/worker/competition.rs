@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use cosmwasm_std::Uint128;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Beacon error fragments which suggest another worker fulfilled the requests first.
+/// A failure is only counted as a lost race once the requests are confirmed inactive.
+pub const DEFAULT_RACE_ERRORS: &[&str] = &["already fulfilled", "no longer active"];
+
+/// Settings for running alongside other workers serving the same beacon.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CompetitionConfig {
+    /// Re-fetch active requests right before broadcasting, defaults to true
+    pub refetch_before_submit: Option<bool>,
+    /// Maximum random delay, in milliseconds, added before each submission
+    pub max_jitter_ms: Option<u64>,
+    /// Number of replicas of this worker sharing the load
+    pub replicas: Option<u64>,
+    /// Index of this replica, can also be set with the `REPLICA_INDEX` environment variable
+    pub replica_index: Option<u64>,
+    /// How long, in milliseconds, a replica waits for the leader of a batch before submitting it itself
+    pub leader_grace_ms: Option<u64>,
+    /// Contract error fragments which suggest another worker fulfilled the requests first
+    pub race_errors: Option<Vec<String>>,
+}
+
+impl CompetitionConfig {
+    pub fn refetch_before_submit(&self) -> bool {
+        self.refetch_before_submit.unwrap_or(true)
+    }
+
+    pub fn jitter(&self) -> Option<Duration> {
+        self.max_jitter_ms
+            .filter(|max| *max > 0)
+            .map(|max| Duration::from_millis(rand::thread_rng().gen_range(0..=max)))
+    }
+
+    /// Whether this replica is responsible for submitting a batch. Leadership is
+    /// derived from the first request id, so every replica agrees without coordination.
+    pub fn is_leader(&self, request_ids: &[Uint128]) -> bool {
+        match (self.replicas, self.replica_index, request_ids.first()) {
+            (Some(replicas), Some(index), Some(id)) if replicas > 1 => {
+                id.u128() % u128::from(replicas) == u128::from(index)
+            }
+            _ => true,
+        }
+    }
+
+    pub fn leader_grace(&self) -> Duration {
+        Duration::from_millis(self.leader_grace_ms.unwrap_or(3000))
+    }
+
+    pub fn is_race_error(&self, error: &str) -> bool {
+        let error = error.to_lowercase();
+        match &self.race_errors {
+            Some(fragments) => fragments.iter().any(|f| error.contains(&f.to_lowercase())),
+            None => DEFAULT_RACE_ERRORS.iter().any(|f| error.contains(f)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leadership_is_split_between_replicas() {
+        let config = |index| CompetitionConfig {
+            replicas: Some(2),
+            replica_index: Some(index),
+            ..Default::default()
+        };
+        let ids = [Uint128::new(3), Uint128::new(4)];
+        assert!(config(1).is_leader(&ids));
+        assert!(!config(0).is_leader(&ids));
+        assert!(CompetitionConfig::default().is_leader(&ids));
+    }
+
+    #[test]
+    fn race_errors_are_matched_case_insensitively() {
+        let config = CompetitionConfig::default();
+        assert!(config.is_race_error("Request 5 Already Fulfilled"));
+        assert!(!config.is_race_error("out of gas"));
+        assert!(!config.is_race_error("account terra1abc not found"));
+
+        let config = CompetitionConfig {
+            race_errors: Some(vec!["Stale Proof".to_string()]),
+            ..Default::default()
+        };
+        assert!(config.is_race_error("execute wasm contract failed: stale proof"));
+        assert!(!config.is_race_error("request not found"));
+    }
+}
@@ -0,0 +1,130 @@
+use clap::Parser;
+use cosmwasm_std::Uint128;
+
+use crate::utils::CLITheme;
+
+use super::journal::{Journal, JournalEvent, JournalRecord};
+
+#[derive(Debug, Parser, Clone)]
+pub struct HistoryCommandOptions {
+    /// Path to the worker journal file
+    #[clap(short, long)]
+    #[clap(default_value = "journal.jsonl")]
+    journal: String,
+    /// Only show entries for this network
+    #[clap(short, long)]
+    network: Option<String>,
+    /// Only show entries involving this request id
+    #[clap(short, long)]
+    request: Option<u128>,
+    /// Maximum number of (most recent) entries to show
+    #[clap(short, long)]
+    #[clap(default_value = "20")]
+    limit: usize,
+    /// Print entries as raw JSON lines
+    #[clap(long)]
+    json: bool,
+}
+
+pub fn history_cmd(options: &HistoryCommandOptions) {
+    let theme = CLITheme::default();
+    let records = Journal::read(&options.journal).unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error reading journal file: "),
+            theme.error.apply_to(e.to_string())
+        );
+        std::process::exit(1);
+    });
+
+    let records = records
+        .into_iter()
+        .filter(|r| options.network.as_ref().is_none_or(|n| &r.network == n))
+        .filter(|r| {
+            options
+                .request
+                .is_none_or(|id| r.event.request_ids().contains(&Uint128::new(id)))
+        })
+        .collect::<Vec<_>>();
+
+    if records.is_empty() {
+        println!("{}", theme.warning.apply_to("No matching journal entries."));
+        return;
+    }
+
+    let skip = records.len().saturating_sub(options.limit);
+    for record in &records[skip..] {
+        if options.json {
+            println!("{}", serde_json::to_string(record).unwrap());
+        } else {
+            print_record(record, &theme);
+        }
+    }
+}
+
+fn print_record(record: &JournalRecord, theme: &CLITheme) {
+    let ids = record
+        .event
+        .request_ids()
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let event = match record.event {
+        JournalEvent::Submitted { .. } => theme.success.apply_to(record.event.name()),
+        JournalEvent::Failed { .. } => theme.error.apply_to(record.event.name()),
+        JournalEvent::LostRace { .. } => theme.warning.apply_to(record.event.name()),
+        _ => theme.normal.apply_to(record.event.name()),
+    };
+    println!(
+        "{} {} {} [{}]",
+        theme.dimmed.apply_to(record.timestamp),
+        theme.highlight.apply_to(&record.network),
+        event,
+        ids
+    );
+    match &record.event {
+        JournalEvent::Observed { .. } => {}
+        JournalEvent::Submitting {
+            key_index, proof, ..
+        } => {
+            println!("    {} {}", theme.dimmed.apply_to("key:"), key_index);
+            println!(
+                "    {} {}",
+                theme.dimmed.apply_to("proof:"),
+                serde_json::to_string(proof).unwrap()
+            );
+        }
+        JournalEvent::Submitted {
+            key_index,
+            tx_hash,
+            gas_used,
+            ..
+        } => {
+            println!("    {} {}", theme.dimmed.apply_to("key:"), key_index);
+            println!("    {} {}", theme.dimmed.apply_to("tx-hash:"), tx_hash);
+            println!("    {} {}", theme.dimmed.apply_to("gas-used:"), gas_used);
+        }
+        JournalEvent::Failed {
+            key_index,
+            tx_hash,
+            gas_used,
+            error,
+            ..
+        } => {
+            println!("    {} {}", theme.dimmed.apply_to("key:"), key_index);
+            if let Some(tx_hash) = tx_hash {
+                println!("    {} {}", theme.dimmed.apply_to("tx-hash:"), tx_hash);
+            }
+            if let Some(gas_used) = gas_used {
+                println!("    {} {}", theme.dimmed.apply_to("gas-used:"), gas_used);
+            }
+            println!("    {} {}", theme.dimmed.apply_to("error:"), error);
+        }
+        JournalEvent::LostRace { tx_hash, .. } => {
+            if let Some(tx_hash) = tx_hash {
+                println!("    {} {}", theme.dimmed.apply_to("tx-hash:"), tx_hash);
+            }
+        }
+    }
+}
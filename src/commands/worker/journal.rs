@@ -0,0 +1,400 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cosmwasm_std::Uint128;
+use ecvrf_rs::Proof;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Submissions without a recorded outcome are considered in flight for this long
+/// after a restart. This comfortably exceeds the transaction timeout height.
+pub const PENDING_TIMEOUT_SECS: u64 = 120;
+
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("IO error: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Error parsing journal entry on line {0}: {1}")]
+    Parse(usize, serde_json::Error),
+    #[error("Error serializing journal entry: {0}")]
+    Serialize(serde_json::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRecord {
+    pub timestamp: u64,
+    pub network: String,
+    #[serde(flatten)]
+    pub event: JournalEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum JournalEvent {
+    /// New active requests were seen on chain
+    Observed { request_ids: Vec<Uint128> },
+    /// A submission is about to be broadcast
+    Submitting {
+        request_ids: Vec<Uint128>,
+        key_index: usize,
+        proof: Proof,
+    },
+    /// A submission was included in a block
+    Submitted {
+        request_ids: Vec<Uint128>,
+        key_index: usize,
+        tx_hash: String,
+        gas_used: u64,
+    },
+    /// A submission failed, either before or after broadcasting
+    Failed {
+        request_ids: Vec<Uint128>,
+        key_index: usize,
+        tx_hash: Option<String>,
+        gas_used: Option<u64>,
+        error: String,
+    },
+    /// Another worker fulfilled the requests first
+    LostRace {
+        request_ids: Vec<Uint128>,
+        tx_hash: Option<String>,
+    },
+}
+
+impl JournalEvent {
+    pub fn request_ids(&self) -> &[Uint128] {
+        match self {
+            JournalEvent::Observed { request_ids }
+            | JournalEvent::Submitting { request_ids, .. }
+            | JournalEvent::Submitted { request_ids, .. }
+            | JournalEvent::Failed { request_ids, .. }
+            | JournalEvent::LostRace { request_ids, .. } => request_ids,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JournalEvent::Observed { .. } => "observed",
+            JournalEvent::Submitting { .. } => "submitting",
+            JournalEvent::Submitted { .. } => "submitted",
+            JournalEvent::Failed { .. } => "failed",
+            JournalEvent::LostRace { .. } => "lost_race",
+        }
+    }
+}
+
+/// State of a single network, reconstructed by replaying the journal.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkState {
+    /// Index of the key that should be used for the next submission
+    pub next_key: usize,
+    /// Requests that have been fulfilled by this worker
+    pub fulfilled: HashSet<u128>,
+    /// Requests with a submission in flight, and when it was started
+    pub pending: HashMap<u128, u64>,
+    /// Requests that have already been recorded as observed
+    pub observed: HashSet<u128>,
+}
+
+impl NetworkState {
+    pub fn apply(&mut self, record: &JournalRecord) {
+        match &record.event {
+            JournalEvent::Observed { request_ids } => {
+                self.observed.extend(request_ids.iter().map(Uint128::u128));
+            }
+            // Lost races are only inferred, so they are retried like an in flight
+            // submission if the requests are still active once it expires.
+            JournalEvent::Submitting { request_ids, .. }
+            | JournalEvent::LostRace { request_ids, .. } => {
+                for id in request_ids {
+                    self.pending.insert(id.u128(), record.timestamp);
+                }
+            }
+            JournalEvent::Submitted {
+                request_ids,
+                key_index,
+                ..
+            } => {
+                for id in request_ids {
+                    self.pending.remove(&id.u128());
+                    self.fulfilled.insert(id.u128());
+                }
+                self.next_key = key_index + 1;
+            }
+            JournalEvent::Failed {
+                request_ids,
+                key_index,
+                ..
+            } => {
+                for id in request_ids {
+                    self.pending.remove(&id.u128());
+                }
+                self.next_key = key_index + 1;
+            }
+        }
+    }
+
+    /// Forgets requests that are no longer active, which can't be served again,
+    /// so the state doesn't grow with every request the worker has seen.
+    pub fn retain_active(&mut self, active: &HashSet<u128>) {
+        self.fulfilled.retain(|id| active.contains(id));
+        self.pending.retain(|id, _| active.contains(id));
+        self.observed.retain(|id| active.contains(id));
+    }
+
+    /// Whether a request should be skipped because it was already handled by this worker.
+    pub fn is_handled(&self, id: u128, now: u64) -> bool {
+        self.fulfilled.contains(&id)
+            || self
+                .pending
+                .get(&id)
+                .is_some_and(|started| now < started + PENDING_TIMEOUT_SECS)
+    }
+}
+
+/// Append-only JSONL log of everything a worker observes and submits. The file
+/// is kept as a history and never truncated: it is replayed in full on startup,
+/// so rotate or remove it while the worker is stopped once it grows too large.
+pub struct Journal {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Journal {
+    pub fn open<P>(path: &P) -> Result<Self, JournalError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        let mut contents = vec![];
+        file.read_to_end(&mut contents)?;
+        match last_line(&contents) {
+            // A torn write from a previous run: drop it so new entries start on a fresh line
+            Some((start, line)) if serde_json::from_slice::<JournalRecord>(line).is_err() => {
+                eprintln!(
+                    "[WARN] Discarding incomplete journal entry at the end of {}",
+                    path.as_ref().display()
+                );
+                file.set_len(start as u64)?;
+            }
+            Some(_) if !contents.ends_with(b"\n") => writeln!(file)?,
+            _ => {}
+        }
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn read<P>(path: &P) -> Result<Vec<JournalRecord>, JournalError>
+    where
+        P: AsRef<Path>,
+    {
+        if !path.as_ref().exists() {
+            return Ok(vec![]);
+        }
+        let reader = BufReader::new(File::open(path)?);
+        let mut lines = vec![];
+        for (i, line) in reader.split(b'\n').enumerate() {
+            let line = line?;
+            if !line.trim_ascii().is_empty() {
+                lines.push((i + 1, line));
+            }
+        }
+        let mut records = vec![];
+        let last = lines.len();
+        for (n, (line_number, line)) in lines.into_iter().enumerate() {
+            match serde_json::from_slice(&line) {
+                Ok(record) => records.push(record),
+                // Only the final entry can be cut short by a crash mid-write
+                Err(e) if n + 1 == last => {
+                    eprintln!(
+                        "[WARN] Skipping incomplete journal entry on line {line_number}: {e}"
+                    );
+                }
+                Err(e) => return Err(JournalError::Parse(line_number, e)),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Replays the journal to reconstruct the state of a network.
+    pub fn network_state(&self, network: &str) -> Result<NetworkState, JournalError> {
+        let mut state = NetworkState::default();
+        Journal::read(&self.path)?
+            .iter()
+            .filter(|r| r.network == network)
+            .for_each(|r| state.apply(r));
+        Ok(state)
+    }
+
+    pub fn append(
+        &self,
+        network: &str,
+        event: JournalEvent,
+    ) -> Result<JournalRecord, JournalError> {
+        let record = JournalRecord {
+            timestamp: now(),
+            network: network.to_string(),
+            event,
+        };
+        let line = serde_json::to_string(&record).map_err(JournalError::Serialize)?;
+        let mut file = self.file.lock().unwrap();
+        writeln!(file, "{line}")?;
+        file.flush()?;
+        Ok(record)
+    }
+}
+
+/// Returns the offset and contents of the last non-blank line.
+fn last_line(contents: &[u8]) -> Option<(usize, &[u8])> {
+    let end = contents.iter().rposition(|b| !b.is_ascii_whitespace())? + 1;
+    let start = contents[..end]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    Some((start, &contents[start..end]))
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: u64, event: JournalEvent) -> JournalRecord {
+        JournalRecord {
+            timestamp,
+            network: "localkujira".to_string(),
+            event,
+        }
+    }
+
+    #[test]
+    fn replay_resumes_key_rotation_and_skips_fulfilled() {
+        let mut state = NetworkState::default();
+        state.apply(&record(
+            0,
+            JournalEvent::Submitted {
+                request_ids: vec![Uint128::new(1), Uint128::new(2)],
+                key_index: 2,
+                tx_hash: "HASH".to_string(),
+                gas_used: 100,
+            },
+        ));
+        assert_eq!(state.next_key, 3);
+        assert!(state.is_handled(1, 0));
+        assert!(!state.is_handled(3, 0));
+    }
+
+    #[test]
+    fn pending_submissions_expire() {
+        let mut state = NetworkState::default();
+        state.apply(&record(
+            100,
+            JournalEvent::Submitting {
+                request_ids: vec![Uint128::new(7)],
+                key_index: 0,
+                proof: Proof::new(&crate::utils::beacon_interface::test_sk(), [0u8; 32]).unwrap(),
+            },
+        ));
+        assert!(state.is_handled(7, 100));
+        assert!(!state.is_handled(7, 100 + PENDING_TIMEOUT_SECS));
+    }
+
+    #[test]
+    fn lost_races_expire_and_inactive_requests_are_forgotten() {
+        let mut state = NetworkState::default();
+        state.apply(&record(
+            100,
+            JournalEvent::LostRace {
+                request_ids: vec![Uint128::new(7), Uint128::new(8)],
+                tx_hash: None,
+            },
+        ));
+        assert!(state.is_handled(7, 100));
+        assert!(!state.is_handled(7, 100 + PENDING_TIMEOUT_SECS));
+
+        state.retain_active(&HashSet::from([8]));
+        assert!(!state.is_handled(7, 100));
+        assert!(state.is_handled(8, 100));
+    }
+
+    fn journal_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "entropycli-journal-{name}-{}.jsonl",
+            std::process::id()
+        ))
+    }
+
+    fn observed(id: u128) -> String {
+        let record = record(
+            0,
+            JournalEvent::Observed {
+                request_ids: vec![Uint128::new(id)],
+            },
+        );
+        serde_json::to_string(&record).unwrap()
+    }
+
+    #[test]
+    fn torn_final_line_is_skipped_and_repaired() {
+        let path = journal_path("torn");
+        let torn = observed(2);
+        std::fs::write(&path, format!("{}\n{}", observed(1), &torn[..10])).unwrap();
+        assert_eq!(Journal::read(&path).unwrap().len(), 1);
+
+        let journal = Journal::open(&path).unwrap();
+        journal
+            .append(
+                "localkujira",
+                JournalEvent::Observed {
+                    request_ids: vec![],
+                },
+            )
+            .unwrap();
+        assert_eq!(Journal::read(&path).unwrap().len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn corrupt_middle_line_fails() {
+        let path = journal_path("corrupt");
+        std::fs::write(&path, format!("{}\n{{\n{}\n", observed(1), observed(2))).unwrap();
+        let result = Journal::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(result, Err(JournalError::Parse(2, _))));
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let record = record(
+            5,
+            JournalEvent::Failed {
+                request_ids: vec![Uint128::new(4)],
+                key_index: 1,
+                tx_hash: None,
+                gas_used: None,
+                error: "Out of gas".to_string(),
+            },
+        );
+        let line = serde_json::to_string(&record).unwrap();
+        let parsed = serde_json::from_str::<JournalRecord>(&line).unwrap();
+        assert_eq!(parsed.event.name(), "failed");
+        assert_eq!(parsed.event.request_ids(), &[Uint128::new(4)]);
+    }
+}
@@ -0,0 +1,160 @@
+//! Backup formats for worker VRF keys.
+//!
+//! * `hex`: one hex encoded 32 byte secret key per line. Empty lines and lines
+//!   starting with `#` are ignored.
+//! * `json`: `{"version": 1, "keys": [{"public_key": "<hex>", "secret_key": "<hex>"}]}`.
+//!   The public key is checked against the secret key when importing.
+//!
+//! Keys can also be derived from a BIP39 mnemonic, so that a single backed-up
+//! seed is enough to reconstruct every key of a worker. Key `i` is the private
+//! key at the BIP32 path `m/44'/118'/1'/0'/{i}'`, which never overlaps with the
+//! default wallet path of a mnemonic.
+
+use ecvrf_rs::{PublicKey, SecretKey};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const BACKUP_VERSION: u32 = 1;
+const VRF_KEY_PATH: &str = "m/44'/118'/1'/0'";
+
+#[derive(Debug, Error)]
+pub enum KeyBackupError {
+    #[error("Invalid JSON key backup: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid hex key on line {0}")]
+    Hex(usize),
+    #[error("Unsupported key backup version {0}")]
+    Version(u32),
+    #[error("Public key {0} does not match its secret key")]
+    Mismatch(PublicKey),
+    #[error("Invalid mnemonic")]
+    InvalidMnemonic,
+    #[error("Derivation Error")]
+    Derivation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum BackupFormat {
+    Hex,
+    Json,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyBackup {
+    version: u32,
+    keys: Vec<KeyBackupEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyBackupEntry {
+    public_key: PublicKey,
+    secret_key: SecretKey,
+}
+
+fn public_key(sk: &SecretKey) -> PublicKey {
+    sk.extract_public_key_and_scalar().unwrap().0
+}
+
+pub fn export(keys: &[SecretKey], format: BackupFormat) -> String {
+    match format {
+        BackupFormat::Hex => keys.iter().map(|sk| sk.to_string() + "\n").collect(),
+        BackupFormat::Json => {
+            let backup = KeyBackup {
+                version: BACKUP_VERSION,
+                keys: keys
+                    .iter()
+                    .map(|sk| KeyBackupEntry {
+                        public_key: public_key(sk),
+                        secret_key: *sk,
+                    })
+                    .collect(),
+            };
+            serde_json::to_string_pretty(&backup).unwrap()
+        }
+    }
+}
+
+/// Parses a key backup, detecting its format from its contents.
+pub fn import(contents: &str) -> Result<Vec<SecretKey>, KeyBackupError> {
+    if contents.trim_start().starts_with('{') {
+        let backup = serde_json::from_str::<KeyBackup>(contents)?;
+        if backup.version != BACKUP_VERSION {
+            return Err(KeyBackupError::Version(backup.version));
+        }
+        return backup
+            .keys
+            .into_iter()
+            .map(|entry| {
+                if public_key(&entry.secret_key) == entry.public_key {
+                    Ok(entry.secret_key)
+                } else {
+                    Err(KeyBackupError::Mismatch(entry.public_key))
+                }
+            })
+            .collect();
+    }
+
+    contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(number, line)| match hex::decode(line) {
+            Ok(bytes) if bytes.len() == 32 => Ok(SecretKey::from_slice(&bytes)),
+            _ => Err(KeyBackupError::Hex(number)),
+        })
+        .collect()
+}
+
+/// Derives the VRF key with the given index from a BIP39 mnemonic.
+pub fn derive(mnemonic: &str, index: u32) -> Result<SecretKey, KeyBackupError> {
+    let seed = bip39::Mnemonic::parse(mnemonic)
+        .map_err(|_| KeyBackupError::InvalidMnemonic)?
+        .to_seed("");
+    let path = format!("{VRF_KEY_PATH}/{index}'")
+        .parse::<bip32::DerivationPath>()
+        .map_err(|_| KeyBackupError::Derivation)?;
+    let privkey =
+        bip32::XPrv::derive_from_path(seed, &path).map_err(|_| KeyBackupError::Derivation)?;
+    Ok(SecretKey::new(&privkey.to_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MNEMONIC: &str = "notice oak worry limit wrap speak medal online prefer cluster roof addict wrist behave treat actual wasp year salad speed social layer crew genius";
+
+    #[test]
+    fn backups_round_trip() {
+        let keys = vec![SecretKey::new(&[1u8; 32]), SecretKey::new(&[2u8; 32])];
+        for format in [BackupFormat::Hex, BackupFormat::Json] {
+            assert_eq!(import(&export(&keys, format)).unwrap(), keys);
+        }
+
+        let hex = format!("# worker keys\n\n{}\n", keys[0]);
+        assert_eq!(import(&hex).unwrap(), vec![keys[0]]);
+        assert!(matches!(
+            import("# worker keys\nnot a key"),
+            Err(KeyBackupError::Hex(2))
+        ));
+
+        let tampered = export(&keys[..1], BackupFormat::Json)
+            .replace(&keys[0].to_string(), &keys[1].to_string());
+        assert!(matches!(
+            import(&tampered),
+            Err(KeyBackupError::Mismatch(_))
+        ));
+    }
+
+    #[test]
+    fn derivation_is_deterministic() {
+        let first = derive(MNEMONIC, 0).unwrap();
+        assert_eq!(first, derive(MNEMONIC, 0).unwrap());
+        assert_ne!(first, derive(MNEMONIC, 1).unwrap());
+        assert!(matches!(
+            derive("not a mnemonic", 0),
+            Err(KeyBackupError::InvalidMnemonic)
+        ));
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use ecvrf_rs::SecretKey;
+use hmac::Hmac;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+
+use crate::utils::CLITheme;
+
+const KEY_FILE_VERSION: u32 = 1;
+const KDF_ITERATIONS: u32 = 200_000;
+
+#[derive(Debug, Error)]
+pub enum KeyFileError {
+    #[error("IO error: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Invalid key file: {0}")]
+    Format(#[from] serde_json::Error),
+    #[error("Invalid key file: {0}")]
+    Hex(#[from] hex::FromHexError),
+    #[error("Unsupported key file version {0}")]
+    Version(u32),
+    #[error("Wrong password, or corrupted key file")]
+    Decrypt,
+}
+
+/// VRF secret keys stored on disk, encrypted with AES-256-GCM under a key
+/// derived from a password with PBKDF2-SHA256.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedKeyFile {
+    version: u32,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn cipher(password: &str, salt: &[u8], iterations: u32) -> Aes256Gcm {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2::<Hmac<Sha256>>(password.as_bytes(), salt, iterations, &mut key);
+    Aes256Gcm::new(&key.into())
+}
+
+/// Reads and decrypts the keys stored in a key file.
+pub fn load<P: AsRef<Path>>(path: P, password: &str) -> Result<Vec<SecretKey>, KeyFileError> {
+    let file = serde_json::from_str::<EncryptedKeyFile>(&std::fs::read_to_string(path)?)?;
+    if file.version != KEY_FILE_VERSION {
+        return Err(KeyFileError::Version(file.version));
+    }
+    let nonce = hex::decode(file.nonce)?;
+    if nonce.len() != 12 {
+        return Err(KeyFileError::Decrypt);
+    }
+    let plaintext = cipher(password, &hex::decode(file.salt)?, file.iterations)
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            hex::decode(file.ciphertext)?.as_ref(),
+        )
+        .map_err(|_| KeyFileError::Decrypt)?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Encrypts and writes keys to a key file, replacing its previous contents.
+pub fn save<P: AsRef<Path>>(
+    path: P,
+    password: &str,
+    keys: &[SecretKey],
+) -> Result<(), KeyFileError> {
+    let mut rng = rand::thread_rng();
+    let salt: [u8; 16] = rng.gen();
+    let nonce: [u8; 12] = rng.gen();
+    let plaintext = serde_json::to_vec(keys)?;
+    let ciphertext = cipher(password, &salt, KDF_ITERATIONS)
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_ref())
+        .map_err(|_| KeyFileError::Decrypt)?;
+    let file = EncryptedKeyFile {
+        version: KEY_FILE_VERSION,
+        iterations: KDF_ITERATIONS,
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&file)?)?;
+    Ok(())
+}
+
+/// Password for key files, from the `KEY_PASSWORD` environment variable or
+/// prompted for. New key files ask for the password twice.
+pub fn password(confirm: bool) -> String {
+    if let Ok(password) = std::env::var("KEY_PASSWORD") {
+        return password;
+    }
+    let theme = CLITheme::default();
+    let mut prompt = dialoguer::Password::with_theme(&theme);
+    prompt.with_prompt("Key file password");
+    if confirm {
+        prompt.with_confirmation("Confirm password", "Passwords do not match");
+    }
+    prompt.interact().unwrap()
+}
+
+/// Decrypts a key file, exiting with an error message on failure.
+pub fn unlock(path: &str) -> Vec<SecretKey> {
+    let theme = CLITheme::default();
+    load(path, &password(false)).unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error reading key file:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_round_trip_through_key_file() {
+        let path = std::env::temp_dir().join(format!("entropy-keys-{}.json", std::process::id()));
+        let keys = vec![SecretKey::new(&[1u8; 32]), SecretKey::new(&[2u8; 32])];
+        save(&path, "hunter2", &keys).unwrap();
+
+        assert_eq!(load(&path, "hunter2").unwrap(), keys);
+        assert!(matches!(load(&path, "wrong"), Err(KeyFileError::Decrypt)));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,7 +1,23 @@
+use std::path::Path;
+
 use clap::{Parser, Subcommand};
-use ecvrf_rs::SecretKey;
+use ecvrf_rs::{PublicKey, SecretKey};
 use rand::Rng;
 
+use crate::{
+    cosmos::wallet::Wallet,
+    utils::{
+        beacon_interface::Beacon,
+        config::{ConfigType, ConfigUtils},
+        CLITheme,
+    },
+};
+
+use super::{
+    key_backup::{self, BackupFormat},
+    key_file,
+    worker_config::WorkerConfig,
+};
 
 #[derive(Debug, Parser, Clone)]
 pub struct KeyCommandOptions {
@@ -12,30 +28,821 @@ pub struct KeyCommandOptions {
 #[derive(Debug, Subcommand, Clone)]
 pub enum KeyCommand {
     #[clap(about = "Generate a key")]
-    New{
+    New {
+        #[clap(short, long)]
+        #[clap(default_value = "1")]
+        /// Number of keys to generate
+        num: u64,
+        /// Add the generated keys to this worker configuration file, instead of printing them
+        #[clap(short, long)]
+        config: Option<String>,
+        /// Network to add the keys to, if it has its own keys. Defaults to the top-level keys
+        #[clap(long, requires = "config")]
+        network: Option<String>,
+        /// Add the generated keys to this encrypted key file, creating it if needed
+        #[clap(short, long, conflicts_with = "config")]
+        key_file: Option<String>,
+    },
+    #[clap(about = "Derive keys deterministically from a mnemonic, read from VRF_MNEMONIC or prompted for")]
+    Derive {
         #[clap(short, long)]
         #[clap(default_value = "1")]
-        /// The name of the key
-        num: u64
+        /// Number of keys to derive
+        num: u32,
+        /// Index of the first key to derive
+        #[clap(short, long)]
+        #[clap(default_value = "0")]
+        index: u32,
+        /// Add the derived keys to this worker configuration file, instead of printing them
+        #[clap(short, long)]
+        config: Option<String>,
+        /// Network to add the keys to, if it has its own keys. Defaults to the top-level keys
+        #[clap(long, requires = "config")]
+        network: Option<String>,
+        /// Add the derived keys to this encrypted key file, creating it if needed
+        #[clap(short, long, conflicts_with = "config")]
+        key_file: Option<String>,
+    },
+    #[clap(about = "Export keys to a backup file, including their secret keys")]
+    Export {
+        /// Path to the configuration file
+        #[clap(short, long)]
+        #[clap(default_value = "config.json")]
+        config: String,
+        /// Network to export keys for. Defaults to the top-level keys
+        #[clap(short, long)]
+        network: Option<String>,
+        /// Export the keys in this encrypted key file instead
+        #[clap(short, long, conflicts_with_all = ["config", "network"])]
+        key_file: Option<String>,
+        /// Backup format
+        #[clap(short, long, value_enum)]
+        #[clap(default_value = "json")]
+        format: BackupFormat,
+        /// File to write the backup to, defaults to standard output
+        #[clap(short, long)]
+        output: Option<String>,
+    },
+    #[clap(about = "Import keys from a hex or JSON backup file")]
+    Import {
+        /// Backup file to import, or `-` for standard input
+        input: String,
+        /// Worker configuration file to add the keys to
+        #[clap(short, long)]
+        #[clap(default_value = "config.json")]
+        config: String,
+        /// Network to add the keys to, if it has its own keys. Defaults to the top-level keys
+        #[clap(short, long)]
+        network: Option<String>,
+        /// Add the keys to this encrypted key file instead
+        #[clap(short, long, conflicts_with_all = ["config", "network"])]
+        key_file: Option<String>,
+    },
+    #[clap(about = "List the public keys in a configuration or key file")]
+    List {
+        /// Path to the configuration file
+        #[clap(short, long)]
+        #[clap(default_value = "config.json")]
+        config: String,
+        /// Network to list keys for. Defaults to the top-level keys
+        #[clap(short, long)]
+        network: Option<String>,
+        /// List the keys in this encrypted key file instead
+        #[clap(short, long, conflicts_with_all = ["config", "network"])]
+        key_file: Option<String>,
+    },
+    #[clap(about = "Whitelist keys on the beacon, paying the required deposit")]
+    Whitelist {
+        /// Keys to whitelist, by index or public key. Defaults to every key in the config and key file
+        keys: Vec<String>,
+        /// Path to the configuration file
+        #[clap(short, long)]
+        #[clap(default_value = "config.json")]
+        config: String,
+        /// Network to use (defined in config). Optional if default network is set in config
+        #[clap(short, long)]
+        network: Option<String>,
+        /// Generate this many new keys, whitelist them and add them to the config file
+        #[clap(long, conflicts_with = "keys")]
+        new: Option<u64>,
+    },
+    #[clap(about = "Show the on-chain status of the keys in the configuration and key file")]
+    Status {
+        /// Path to the configuration file
+        #[clap(short, long)]
+        #[clap(default_value = "config.json")]
+        config: String,
+        /// Network to use (defined in config). Optional if default network is set in config
+        #[clap(short, long)]
+        network: Option<String>,
+    },
+    #[clap(about = "Retire a key, reclaiming its deposit and removing it from the configuration or key file")]
+    Remove {
+        /// The key to remove, by index or public key
+        key: String,
+        /// Path to the configuration file
+        #[clap(short, long)]
+        #[clap(default_value = "config.json")]
+        config: String,
+        /// Network to use (defined in config). Optional if default network is set in config
+        #[clap(short, long)]
+        network: Option<String>,
+        /// Keep the key in the configuration or key file after reclaiming its deposit
+        #[clap(long)]
+        keep: bool,
     },
 }
 
-pub fn key_cmd(options: &KeyCommandOptions) {
+pub async fn key_cmd(options: KeyCommandOptions) {
     match options.command {
-        KeyCommand::New{num} => new_key(num),
+        KeyCommand::New {
+            num,
+            config,
+            network,
+            key_file,
+        } => new_key(
+            num,
+            config.as_deref(),
+            network.as_deref(),
+            key_file.as_deref(),
+        ),
+        KeyCommand::Derive {
+            num,
+            index,
+            config,
+            network,
+            key_file,
+        } => derive_keys(
+            num,
+            index,
+            config.as_deref(),
+            network.as_deref(),
+            key_file.as_deref(),
+        ),
+        KeyCommand::Export {
+            config,
+            network,
+            key_file,
+            format,
+            output,
+        } => export_keys(
+            &config,
+            network.as_deref(),
+            key_file.as_deref(),
+            format,
+            output.as_deref(),
+        ),
+        KeyCommand::Import {
+            input,
+            config,
+            network,
+            key_file,
+        } => import_keys(
+            &input,
+            Some(config.as_str()),
+            network.as_deref(),
+            key_file.as_deref(),
+        ),
+        KeyCommand::List {
+            config,
+            network,
+            key_file,
+        } => list_keys(&config, network.as_deref(), key_file.as_deref()),
+        KeyCommand::Whitelist {
+            keys,
+            config,
+            network,
+            new,
+        } => whitelist_keys(&keys, &config, network, new).await,
+        KeyCommand::Status { config, network } => key_status(&config, network).await,
+        KeyCommand::Remove {
+            key,
+            config,
+            network,
+            keep,
+        } => remove_key(&key, &config, network, keep).await,
     }
 }
 
-fn new_key(num: u64) {
+fn generate_keys(num: u64) -> Vec<SecretKey> {
     let mut rng = rand::thread_rng();
-    for _ in 0..num {
-        let mut key = [0u8; 32];
-        rng.fill(&mut key);
-    
-        let sk = SecretKey::new(&key);
-        let pk = sk.extract_public_key_and_scalar().unwrap().0;
-    
-        println!("Public key: {}", pk);
-        println!("Secret key: {}", sk);
-    }
-}
\ No newline at end of file
+    (0..num)
+        .map(|_| {
+            let mut key = [0u8; 32];
+            rng.fill(&mut key);
+            SecretKey::new(&key)
+        })
+        .collect()
+}
+
+/// Adds keys to a key file or configuration file, if either is given. Returns
+/// whether the keys were stored.
+fn store_keys(
+    keys: &[SecretKey],
+    config: Option<&str>,
+    network: Option<&str>,
+    key_file: Option<&str>,
+) -> bool {
+    let theme = CLITheme::default();
+    if let Some(path) = key_file {
+        let exists = Path::new(path).exists();
+        let password = key_file::password(!exists);
+        let mut stored = if exists {
+            key_file::load(path, &password).unwrap_or_else(|e| {
+                eprintln!(
+                    "{} {}",
+                    theme.error.apply_to("Error reading key file:"),
+                    theme.highlight.apply_to(e)
+                );
+                std::process::exit(1);
+            })
+        } else {
+            vec![]
+        };
+        let new = keys
+            .iter()
+            .filter(|sk| !stored.contains(sk))
+            .copied()
+            .collect::<Vec<_>>();
+        stored.extend(new);
+        key_file::save(path, &password, &stored).unwrap_or_else(|e| {
+            eprintln!(
+                "{} {}",
+                theme.error.apply_to("Error writing key file:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+    } else if let Some(path) = config {
+        let mut worker_config = load_worker_config(path);
+        if let Some(network) = network {
+            if !worker_config.networks.contains_key(network) {
+                eprintln!(
+                    "{} {} {}",
+                    theme.error.apply_to("Network"),
+                    theme.highlight.apply_to(network),
+                    theme.error.apply_to("not found in config file.")
+                );
+                std::process::exit(1);
+            }
+        }
+        let stored = worker_config.keys_for_mut(network.unwrap_or_default());
+        let new = keys
+            .iter()
+            .filter(|sk| !stored.contains(sk))
+            .copied()
+            .collect::<Vec<_>>();
+        stored.extend(new);
+        save_config(&worker_config, path);
+    } else {
+        return false;
+    }
+
+    for sk in keys {
+        println!("Public key: {}", public_key(sk));
+    }
+    println!(
+        "{}",
+        theme
+            .dimmed
+            .apply_to(format!("Saved {} key(s).", keys.len()))
+    );
+    true
+}
+
+fn new_key(num: u64, config: Option<&str>, network: Option<&str>, key_file: Option<&str>) {
+    let keys = generate_keys(num);
+    if !store_keys(&keys, config, network, key_file) {
+        for sk in keys {
+            println!("Public key: {}", public_key(&sk));
+            println!("Secret key: {sk}");
+        }
+    }
+}
+
+fn derive_keys(
+    num: u32,
+    index: u32,
+    config: Option<&str>,
+    network: Option<&str>,
+    key_file: Option<&str>,
+) {
+    let theme = CLITheme::default();
+    let mnemonic = std::env::var("VRF_MNEMONIC").unwrap_or_else(|_| {
+        dialoguer::Password::with_theme(&theme)
+            .with_prompt("Key seed mnemonic")
+            .interact()
+            .unwrap()
+    });
+    let keys = (index..index.saturating_add(num))
+        .map(|i| key_backup::derive(&mnemonic, i))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_or_else(|e| {
+            eprintln!(
+                "{} {}",
+                theme.error.apply_to("Error deriving keys:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+    if !store_keys(&keys, config, network, key_file) {
+        for (i, sk) in (index..).zip(keys) {
+            println!("{}", theme.dimmed.apply_to(format!("Index {i}")));
+            println!("Public key: {}", public_key(&sk));
+            println!("Secret key: {sk}");
+        }
+    }
+}
+
+fn export_keys(
+    config_path: &str,
+    network: Option<&str>,
+    key_file: Option<&str>,
+    format: BackupFormat,
+    output: Option<&str>,
+) {
+    let theme = CLITheme::default();
+    let (mut keys, file_keys) = read_keys(config_path, network, key_file);
+    keys.extend(file_keys);
+    let backup = key_backup::export(&keys, format);
+    match output {
+        Some(path) => {
+            std::fs::write(path, backup).unwrap_or_else(|e| {
+                eprintln!(
+                    "{} {}",
+                    theme.error.apply_to("Error writing key backup:"),
+                    theme.highlight.apply_to(e)
+                );
+                std::process::exit(1);
+            });
+            println!(
+                "{}",
+                theme
+                    .dimmed
+                    .apply_to(format!("Exported {} key(s) to {path}.", keys.len()))
+            );
+        }
+        None => print!("{backup}"),
+    }
+}
+
+fn import_keys(input: &str, config: Option<&str>, network: Option<&str>, key_file: Option<&str>) {
+    let theme = CLITheme::default();
+    let contents = if input == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(input)
+    }
+    .unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error reading key backup:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+    let keys = key_backup::import(&contents).unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error reading key backup:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+    if keys.is_empty() {
+        println!("{}", theme.warning.apply_to("No keys found in backup."));
+        return;
+    }
+    store_keys(&keys, config, network, key_file);
+}
+
+/// Keys from a key file, or from a configuration file along with the keys of
+/// the key file it references.
+fn read_keys(
+    config_path: &str,
+    network: Option<&str>,
+    key_file: Option<&str>,
+) -> (Vec<SecretKey>, Vec<SecretKey>) {
+    if let Some(path) = key_file {
+        return (key_file::unlock(path), vec![]);
+    }
+    let config = load_worker_config(config_path);
+    let file_keys = config
+        .key_file
+        .as_deref()
+        .map(key_file::unlock)
+        .unwrap_or_default();
+    (config.keys_for(network.unwrap_or_default()).clone(), file_keys)
+}
+
+/// The keys a worker uses on a network, in rotation order: those in the
+/// configuration file, followed by those in the key file it references. The key
+/// file password is returned too, so the key file can be rewritten without
+/// asking for it again.
+fn worker_keys(config: &WorkerConfig, network: &str) -> (Vec<SecretKey>, Option<String>) {
+    let mut keys = config.keys_for(network).clone();
+    let Some(path) = &config.key_file else {
+        return (keys, None);
+    };
+    let password = key_file::password(false);
+    keys.extend(key_file::load(path, &password).unwrap_or_else(|e| {
+        let theme = CLITheme::default();
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error reading key file:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    }));
+    (keys, Some(password))
+}
+
+fn list_keys(config_path: &str, network: Option<&str>, key_file: Option<&str>) {
+    let theme = CLITheme::default();
+    let (keys, file_keys) = read_keys(config_path, network, key_file);
+
+    if keys.is_empty() && file_keys.is_empty() {
+        println!("{}", theme.warning.apply_to("No keys found."));
+        return;
+    }
+
+    println!("{}", theme.highlight.apply_to("Keys:"));
+    for (i, sk) in keys.iter().enumerate() {
+        println!("  {}: {}", theme.dimmed.apply_to(i), public_key(sk));
+    }
+    if !file_keys.is_empty() {
+        println!("{}", theme.highlight.apply_to("Key file:"));
+        for (i, sk) in file_keys.iter().enumerate() {
+            println!("  {}: {}", theme.dimmed.apply_to(i), public_key(sk));
+        }
+    }
+}
+
+fn public_key(sk: &SecretKey) -> PublicKey {
+    sk.extract_public_key_and_scalar().unwrap().0
+}
+
+/// Finds a key by its index in the list, or by its public key.
+fn find_key(keys: &[SecretKey], selector: &str) -> Option<usize> {
+    if let Ok(index) = selector.parse::<usize>() {
+        return (index < keys.len()).then_some(index);
+    }
+    keys.iter()
+        .position(|sk| public_key(sk).to_string().eq_ignore_ascii_case(selector))
+}
+
+fn load_worker_config(config_path: &str) -> WorkerConfig {
+    let theme = CLITheme::default();
+    let config = ConfigUtils::load(&config_path).unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error loading config file: "),
+            theme.error.apply_to(e.to_string())
+        );
+        std::process::exit(1);
+    });
+    let ConfigType::Worker(config) = config else {
+        eprintln!(
+            "{}",
+            theme
+                .error
+                .apply_to("Config file is not a worker config file.")
+        );
+        std::process::exit(1);
+    };
+    config
+}
+
+fn init_key_cmd(config_path: &str, network: Option<String>) -> (WorkerConfig, String, Beacon) {
+    let theme = CLITheme::default();
+    let config = load_worker_config(config_path);
+
+    let network_name = network
+        .or_else(|| config.default_network.clone())
+        .or_else(|| std::env::var("NETWORK").ok())
+        .unwrap_or_else(|| {
+            eprintln!(
+                "{}",
+                theme.error.apply_to("No network specified. Please specify a network with the --network flag or set a default network in the config file.")
+            );
+            std::process::exit(1);
+        });
+
+    let network_info = config.networks.get(&network_name).unwrap_or_else(|| {
+        eprintln!(
+            "{} {} {}",
+            theme.error.apply_to("Network"),
+            theme.highlight.apply_to(&network_name),
+            theme.error.apply_to("not found in config file.")
+        );
+        std::process::exit(1);
+    });
+
+    let beacon_address = network_info
+        .network
+        .deployed_beacon_address
+        .clone()
+        .unwrap_or_else(|| {
+            eprintln!(
+                "{} {}",
+                theme.error.apply_to("No beacon address found for network"),
+                theme.highlight.apply_to(&network_name),
+            );
+            std::process::exit(1);
+        });
+
+    let mnemonic = network_info
+        .signer_mnemonic
+        .clone()
+        .or_else(|| std::env::var("MNEMONIC").ok())
+        .unwrap_or_else(|| {
+            eprintln!(
+                "{}",
+                theme.error.apply_to("No mnemonic set, please set the mnemonic in the config file, or set the MNEMONIC environment variable")
+            );
+            std::process::exit(1);
+        });
+
+    let wallet = Wallet::new(mnemonic, network_info.network.clone()).unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error creating wallet:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+
+    let beacon = Beacon::new(network_info.network.clone(), wallet, beacon_address);
+    (config, network_name, beacon)
+}
+
+fn save_config(config: &WorkerConfig, path: &str) {
+    let theme = CLITheme::default();
+    ConfigUtils::save(config, &path).unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error updating config file: "),
+            theme.error.apply_to(e.to_string())
+        );
+        std::process::exit(1);
+    });
+}
+
+#[allow(clippy::too_many_lines)]
+async fn whitelist_keys(
+    selectors: &[String],
+    config_path: &str,
+    network: Option<String>,
+    new: Option<u64>,
+) {
+    let theme = CLITheme::default();
+    let (mut config, network_name, beacon) = init_key_cmd(config_path, network);
+
+    let beacon_config = beacon.fetch_config().await.unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error fetching beacon config:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+
+    let keys = if let Some(num) = new {
+        generate_keys(num)
+    } else if selectors.is_empty() {
+        worker_keys(&config, &network_name).0
+    } else {
+        let (registered, _) = worker_keys(&config, &network_name);
+        selectors
+            .iter()
+            .map(|selector| {
+                let index = find_key(&registered, selector).unwrap_or_else(|| {
+                    eprintln!(
+                        "{} {}",
+                        theme.error.apply_to("Key not found in config or key file:"),
+                        theme.highlight.apply_to(selector)
+                    );
+                    std::process::exit(1);
+                });
+                registered[index]
+            })
+            .collect()
+    };
+
+    if keys.is_empty() {
+        println!("{}", theme.warning.apply_to("No keys to whitelist."));
+        return;
+    }
+
+    println!(
+        "{} {} {}",
+        theme.dimmed.apply_to("Whitelist deposit:"),
+        beacon_config.whitelist_deposit_amt,
+        beacon_config.native_denom
+    );
+
+    for sk in keys {
+        let pk = public_key(&sk);
+        if new.is_none() {
+            match beacon.fetch_key_status(pk).await {
+                Ok(status) if status.whitelisted => {
+                    println!(
+                        "{} {}",
+                        theme.highlight.apply_to(pk),
+                        theme.dimmed.apply_to("already whitelisted, skipping")
+                    );
+                    continue;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!(
+                        "{} {}",
+                        theme.error.apply_to("Error fetching key status:"),
+                        theme.highlight.apply_to(e)
+                    );
+                    std::process::exit(1);
+                }
+            }
+        }
+
+        let res = beacon
+            .whitelist_key(
+                pk,
+                beacon_config.whitelist_deposit_amt,
+                &beacon_config.native_denom,
+            )
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "{} {}",
+                    theme.error.apply_to("Error whitelisting key:"),
+                    theme.highlight.apply_to(e)
+                );
+                std::process::exit(1);
+            });
+
+        println!(
+            "{} {} {}",
+            theme.highlight.apply_to(pk),
+            theme.success.apply_to("whitelisted"),
+            theme.dimmed.apply_to(res.txhash)
+        );
+
+        if new.is_some() {
+            // Save after every key, so paid deposits are never lost with an unsaved key
+            config.keys_for_mut(&network_name).push(sk);
+            save_config(&config, config_path);
+        }
+    }
+
+    if beacon_config.key_activation_delay > 0 {
+        println!(
+            "{}",
+            theme.dimmed.apply_to(format!(
+                "Keys become active after {} blocks.",
+                beacon_config.key_activation_delay
+            ))
+        );
+    }
+    if new.is_some() {
+        println!(
+            "{}",
+            theme
+                .dimmed
+                .apply_to("Updated config file with whitelisted keys.")
+        );
+    }
+}
+
+async fn key_status(config_path: &str, network: Option<String>) {
+    let theme = CLITheme::default();
+    let (config, network_name, beacon) = init_key_cmd(config_path, network);
+
+    let height = beacon.signer.block_height().await.unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error fetching block height:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+
+    let (keys, _) = worker_keys(&config, &network_name);
+    if keys.is_empty() {
+        println!("{}", theme.warning.apply_to("No keys in config or key file."));
+        return;
+    }
+    let config_keys = config.keys_for(&network_name).len();
+
+    println!("{}", theme.highlight.apply_to("Keys:"));
+    for (i, sk) in keys.iter().enumerate() {
+        let pk = public_key(sk);
+        let status = beacon.fetch_key_status(pk).await.unwrap_or_else(|e| {
+            eprintln!(
+                "{} {}",
+                theme.error.apply_to("Error fetching key status:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+        let status = if status.active {
+            theme.success.apply_to("active".to_string())
+        } else if status.whitelisted {
+            theme.warning.apply_to(format!(
+                "pending, activates at height {} ({} blocks)",
+                status.activation_height,
+                status.activation_height.saturating_sub(u64::from(height))
+            ))
+        } else {
+            theme.error.apply_to("not whitelisted".to_string())
+        };
+        let source = if i < config_keys { "" } else { " (key file)" };
+        println!(
+            "  {}: {} {}{}",
+            theme.dimmed.apply_to(i),
+            pk,
+            status,
+            theme.dimmed.apply_to(source)
+        );
+    }
+}
+
+async fn remove_key(selector: &str, config_path: &str, network: Option<String>, keep: bool) {
+    let theme = CLITheme::default();
+    let (mut config, network_name, beacon) = init_key_cmd(config_path, network);
+
+    let (keys, password) = worker_keys(&config, &network_name);
+    let index = find_key(&keys, selector).unwrap_or_else(|| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Key not found in config or key file:"),
+            theme.highlight.apply_to(selector)
+        );
+        std::process::exit(1);
+    });
+    let pk = public_key(&keys[index]);
+
+    let status = beacon.fetch_key_status(pk).await.unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error fetching key status:"),
+            theme.highlight.apply_to(e)
+        );
+        std::process::exit(1);
+    });
+
+    if status.whitelisted {
+        let res = beacon.reclaim_deposit(pk).await.unwrap_or_else(|e| {
+            eprintln!(
+                "{} {}",
+                theme.error.apply_to("Error reclaiming deposit:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+        println!(
+            "{} {} {}",
+            theme.highlight.apply_to(pk),
+            theme.success.apply_to("retired"),
+            theme.dimmed.apply_to(res.txhash)
+        );
+    } else {
+        println!(
+            "{} {}",
+            theme.highlight.apply_to(pk),
+            theme.dimmed.apply_to("is not whitelisted, nothing to reclaim")
+        );
+    }
+
+    if keep {
+        return;
+    }
+    let config_keys = config.keys_for(&network_name).len();
+    if index < config_keys {
+        config.keys_for_mut(&network_name).remove(index);
+        save_config(&config, config_path);
+        println!(
+            "{}",
+            theme
+                .dimmed
+                .apply_to("Removed key and saved updated config file.")
+        );
+    } else if let (Some(path), Some(password)) = (&config.key_file, password) {
+        let mut file_keys = keys[config_keys..].to_vec();
+        file_keys.remove(index - config_keys);
+        key_file::save(path, &password, &file_keys).unwrap_or_else(|e| {
+            eprintln!(
+                "{} {}",
+                theme.error.apply_to("Error writing key file:"),
+                theme.highlight.apply_to(e)
+            );
+            std::process::exit(1);
+        });
+        println!(
+            "{}",
+            theme
+                .dimmed
+                .apply_to("Removed key and saved updated key file.")
+        );
+    }
+}
@@ -1,9 +1,17 @@
 use clap::{Parser, Subcommand};
 
 pub mod worker_config;
+pub mod competition;
+pub mod history;
+pub mod journal;
+pub mod key_backup;
+pub mod key_file;
 pub mod keys;
+pub mod policy;
+pub mod rotation;
 pub mod start;
 
+use history::{history_cmd, HistoryCommandOptions};
 use start::{start_cmd, StartCommandOptions};
 use keys::{key_cmd, KeyCommandOptions};
 
@@ -20,11 +28,14 @@ pub enum WorkerCommand {
     Keys(KeyCommandOptions),
     #[clap(about = "Start a worker")]
     Start(StartCommandOptions),
+    #[clap(about = "Show the submission history of a worker")]
+    History(HistoryCommandOptions),
 }
 
 pub async fn worker_cmd(options: WorkerCommandOptions) {
     match options.command {
-        WorkerCommand::Keys(options) => key_cmd(&options),
+        WorkerCommand::Keys(options) => key_cmd(options).await,
         WorkerCommand::Start(options) => start_cmd(options).await,
+        WorkerCommand::History(options) => history_cmd(&options),
     }
 }
\ No newline at end of file
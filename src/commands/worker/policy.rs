@@ -0,0 +1,369 @@
+use cosmwasm_std::Uint128;
+use entropy_beacon_cosmos::{beacon::BEACON_BASE_GAS, provide::ActiveRequestInfo};
+use serde::{Deserialize, Serialize};
+
+use crate::cosmos::utils::mul_gas_float;
+
+/// Rules deciding which active requests a worker submits entropy for.
+///
+/// The default policy submits every request in as few batches as the gas limit
+/// allows, as long as the bounties of each batch cover its gas cost.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SubmissionPolicy {
+    /// Minimum margin of payout over gas cost, e.g. `0.1` requires a 10% profit
+    pub min_margin: Option<f64>,
+    /// Evaluate profitability of each request, instead of all-or-nothing for the batch
+    pub per_request: Option<bool>,
+    /// Maximum total callback gas submitted in a single batch
+    pub max_callback_gas: Option<u64>,
+    /// Only fulfil requests submitted by these addresses
+    pub allowed_requesters: Option<Vec<String>>,
+    /// Never fulfil requests submitted by these addresses
+    pub denied_requesters: Option<Vec<String>>,
+    /// Fulfil unprofitable requests anyway once they are this many blocks old
+    pub submit_after_blocks: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    Requester,
+    Unprofitable,
+    GasLimit,
+    TxGasLimit,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Requester => write!(f, "requester not allowed"),
+            SkipReason::Unprofitable => write!(f, "unprofitable"),
+            SkipReason::GasLimit => write!(f, "exceeds batch gas limit"),
+            SkipReason::TxGasLimit => write!(f, "exceeds the maximum gas per transaction"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Requests to submit entropy for, one batch per transaction
+    pub batches: Vec<Vec<ActiveRequestInfo>>,
+    pub skipped: Vec<(Uint128, SkipReason)>,
+}
+
+impl Selection {
+    pub fn requests(&self) -> impl Iterator<Item = &ActiveRequestInfo> {
+        self.batches.iter().flatten()
+    }
+
+    /// Total gas of the selection, including the base gas of every batch.
+    pub fn gas(&self) -> u64 {
+        self.batches.iter().map(|batch| batch_gas(batch)).sum()
+    }
+
+    pub fn payout(&self) -> u128 {
+        self.batches.iter().map(|batch| batch_payout(batch)).sum()
+    }
+}
+
+/// Gas of a batch, including the base gas of the beacon.
+fn batch_gas(batch: &[ActiveRequestInfo]) -> u64 {
+    BEACON_BASE_GAS + batch.iter().map(|r| r.callback_gas_limit).sum::<u64>()
+}
+
+fn batch_payout(batch: &[ActiveRequestInfo]) -> u128 {
+    batch.iter().map(|r| r.submitted_bounty_amount.u128()).sum()
+}
+
+/// Splits requests into batches whose callback gas does not exceed
+/// `max_callback_gas`, and whose total gas (including the base gas of the beacon)
+/// does not exceed `max_tx_gas`. Requests that can never fit in a single batch are
+/// returned separately, with the limit they exceed.
+fn split(
+    requests: Vec<ActiveRequestInfo>,
+    max_tx_gas: Option<u64>,
+    max_callback_gas: Option<u64>,
+) -> (Vec<Vec<ActiveRequestInfo>>, Vec<(Uint128, SkipReason)>) {
+    let mut batches: Vec<Vec<ActiveRequestInfo>> = vec![];
+    let mut oversized = vec![];
+    let mut callback_gas = 0;
+    for request in requests {
+        let gas = request.callback_gas_limit;
+        if max_tx_gas.is_some_and(|max| BEACON_BASE_GAS + gas > max) {
+            oversized.push((request.id, SkipReason::TxGasLimit));
+            continue;
+        }
+        if max_callback_gas.is_some_and(|max| gas > max) {
+            oversized.push((request.id, SkipReason::GasLimit));
+            continue;
+        }
+        let fits = max_tx_gas.is_none_or(|max| BEACON_BASE_GAS + callback_gas + gas <= max)
+            && max_callback_gas.is_none_or(|max| callback_gas + gas <= max);
+        match batches.last_mut() {
+            Some(batch) if fits => {
+                callback_gas += gas;
+                batch.push(request);
+            }
+            _ => {
+                callback_gas = gas;
+                batches.push(vec![request]);
+            }
+        }
+    }
+    (batches, oversized)
+}
+
+impl SubmissionPolicy {
+    /// Whether the policy needs the current block height to make a decision.
+    pub fn needs_height(&self) -> bool {
+        self.submit_after_blocks.is_some()
+    }
+
+    fn requester_allowed(&self, request: &ActiveRequestInfo) -> bool {
+        let submitter = request.submitter.as_str();
+        let allowed = self
+            .allowed_requesters
+            .as_ref()
+            .is_none_or(|allowed| allowed.iter().any(|a| a == submitter));
+        let denied = self
+            .denied_requesters
+            .as_ref()
+            .is_some_and(|denied| denied.iter().any(|d| d == submitter));
+        allowed && !denied
+    }
+
+    fn is_stale(&self, request: &ActiveRequestInfo, height: Option<u64>) -> bool {
+        match (self.submit_after_blocks, height) {
+            (Some(blocks), Some(height)) => {
+                height.saturating_sub(request.submitted_block_height) >= blocks
+            }
+            _ => false,
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn covers(&self, payout: u128, gas: u64, gas_price: f64) -> bool {
+        let cost = mul_gas_float(gas, gas_price).value() as f64;
+        payout as f64 >= cost * (1.0 + self.min_margin.unwrap_or(0.0))
+    }
+
+    /// Selects the requests to submit entropy for, split into batches of at most
+    /// `max_tx_gas` and `max_callback_gas`, in the order they should be submitted.
+    pub fn select(
+        &self,
+        requests: Vec<ActiveRequestInfo>,
+        gas_price: f64,
+        is_subsidized: bool,
+        height: Option<u64>,
+        max_tx_gas: Option<u64>,
+    ) -> Selection {
+        let mut selection = Selection::default();
+
+        let (allowed, denied): (Vec<_>, Vec<_>) = requests
+            .into_iter()
+            .partition(|r| self.requester_allowed(r));
+        selection
+            .skipped
+            .extend(denied.iter().map(|r| (r.id, SkipReason::Requester)));
+
+        // Stale requests are submitted regardless of profitability, and take priority.
+        let (stale, fresh): (Vec<_>, Vec<_>) =
+            allowed.into_iter().partition(|r| self.is_stale(r, height));
+        let mut candidates = stale;
+        candidates.extend(fresh);
+
+        let (batches, oversized) = split(candidates, max_tx_gas, self.max_callback_gas);
+        selection.skipped.extend(oversized);
+
+        // Each batch is a transaction of its own, so its base gas must be paid for
+        // by its own requests, unless stale requests force it.
+        for mut batch in batches {
+            if !is_subsidized && self.per_request.unwrap_or(false) {
+                let base_gas_share = BEACON_BASE_GAS / batch.len() as u64;
+                let (profitable, unprofitable): (Vec<_>, Vec<_>) =
+                    batch.into_iter().partition(|r| {
+                        self.is_stale(r, height)
+                            || self.covers(
+                                r.submitted_bounty_amount.u128(),
+                                r.callback_gas_limit + base_gas_share,
+                                gas_price,
+                            )
+                    });
+                selection.skipped.extend(
+                    unprofitable
+                        .iter()
+                        .map(|r| (r.id, SkipReason::Unprofitable)),
+                );
+                batch = profitable;
+            }
+            if batch.is_empty() {
+                continue;
+            }
+            let has_stale = batch.iter().any(|r| self.is_stale(r, height));
+            if !is_subsidized
+                && !has_stale
+                && !self.covers(batch_payout(&batch), batch_gas(&batch), gas_price)
+            {
+                selection
+                    .skipped
+                    .extend(batch.iter().map(|r| (r.id, SkipReason::Unprofitable)));
+                continue;
+            }
+            selection.batches.push(batch);
+        }
+
+        selection
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::Addr;
+
+    use super::*;
+
+    fn request(id: u128, gas: u64, bounty: u128, height: u64) -> ActiveRequestInfo {
+        ActiveRequestInfo {
+            id: Uint128::new(id),
+            callback_gas_limit: gas,
+            callback_address: Addr::unchecked("callback"),
+            submitter: Addr::unchecked(format!("submitter{id}")),
+            submitted_block_height: height,
+            submitted_bounty_amount: Uint128::new(bounty),
+        }
+    }
+
+    #[test]
+    fn default_policy_is_all_or_nothing() {
+        let policy = SubmissionPolicy::default();
+        let requests = vec![request(1, 100_000, 1_000_000, 0), request(2, 100_000, 0, 0)];
+        let selection = policy.select(requests.clone(), 1.0, false, None, None);
+        assert_eq!(selection.requests().count(), 2);
+
+        let selection = policy.select(requests, 10.0, false, None, None);
+        assert!(selection.batches.is_empty());
+        assert_eq!(selection.skipped.len(), 2);
+    }
+
+    #[test]
+    fn per_request_drops_unprofitable_requests() {
+        let policy = SubmissionPolicy {
+            per_request: Some(true),
+            ..Default::default()
+        };
+        let requests = vec![request(1, 100_000, 1_000_000, 0), request(2, 100_000, 0, 0)];
+        let selection = policy.select(requests, 1.0, false, None, None);
+        assert_eq!(selection.requests().count(), 1);
+        assert_eq!(
+            selection.skipped,
+            vec![(Uint128::new(2), SkipReason::Unprofitable)]
+        );
+    }
+
+    #[test]
+    fn stale_requests_are_submitted_anyway() {
+        let policy = SubmissionPolicy {
+            submit_after_blocks: Some(10),
+            ..Default::default()
+        };
+        let requests = vec![request(1, 100_000, 0, 0)];
+        assert!(policy
+            .select(requests.clone(), 1.0, false, Some(5), None)
+            .batches
+            .is_empty());
+        assert_eq!(
+            policy
+                .select(requests, 1.0, false, Some(10), None)
+                .requests()
+                .count(),
+            1
+        );
+    }
+
+    fn ids(selection: &Selection) -> Vec<Vec<u128>> {
+        selection
+            .batches
+            .iter()
+            .map(|b| b.iter().map(|r| r.id.u128()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn batches_respect_max_tx_gas() {
+        let requests = vec![
+            request(1, 100_000, 0, 0),
+            request(2, 100_000, 0, 0),
+            request(3, 100_000, 0, 0),
+            request(4, 1_000_000, 0, 0),
+        ];
+        let policy = SubmissionPolicy::default();
+        let selection = policy.select(
+            requests.clone(),
+            1.0,
+            true,
+            None,
+            Some(BEACON_BASE_GAS + 200_000),
+        );
+        assert_eq!(ids(&selection), vec![vec![1, 2], vec![3]]);
+        assert_eq!(
+            selection.skipped,
+            vec![(Uint128::new(4), SkipReason::TxGasLimit)]
+        );
+
+        let selection = policy.select(requests, 1.0, true, None, None);
+        assert_eq!(selection.batches.len(), 1);
+        assert!(selection.skipped.is_empty());
+    }
+
+    #[test]
+    fn profitability_is_checked_per_batch() {
+        let max_tx_gas = Some(BEACON_BASE_GAS + 100_000);
+        let requests = vec![
+            request(1, 100_000, 1_000_000, 0),
+            request(2, 100_000, 100_000, 0),
+        ];
+        // Together the bounties would cover both batches, but the second can't pay its own base gas
+        let selection =
+            SubmissionPolicy::default().select(requests.clone(), 1.0, false, None, max_tx_gas);
+        assert_eq!(ids(&selection), vec![vec![1]]);
+        assert_eq!(
+            selection.skipped,
+            vec![(Uint128::new(2), SkipReason::Unprofitable)]
+        );
+
+        let policy = SubmissionPolicy {
+            per_request: Some(true),
+            ..Default::default()
+        };
+        let selection = policy.select(requests, 1.0, false, None, max_tx_gas);
+        assert_eq!(ids(&selection), vec![vec![1]]);
+        assert_eq!(
+            selection.skipped,
+            vec![(Uint128::new(2), SkipReason::Unprofitable)]
+        );
+    }
+
+    #[test]
+    fn requester_lists_and_gas_limit_are_applied() {
+        let policy = SubmissionPolicy {
+            denied_requesters: Some(vec!["submitter1".to_string()]),
+            max_callback_gas: Some(150_000),
+            ..Default::default()
+        };
+        let requests = vec![
+            request(1, 100_000, 0, 0),
+            request(2, 100_000, 0, 0),
+            request(3, 100_000, 0, 0),
+            request(4, 200_000, 0, 0),
+        ];
+        // The limit applies to each batch, so requests beyond it go in another batch
+        let selection = policy.select(requests, 1.0, true, None, None);
+        assert_eq!(ids(&selection), vec![vec![2], vec![3]]);
+        assert_eq!(
+            selection.skipped,
+            vec![
+                (Uint128::new(1), SkipReason::Requester),
+                (Uint128::new(4), SkipReason::GasLimit)
+            ]
+        );
+    }
+}
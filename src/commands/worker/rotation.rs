@@ -0,0 +1,226 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RotationStrategy {
+    /// Use every usable key in turn
+    #[default]
+    RoundRobin,
+    /// Use keys in proportion to their weight
+    Weighted,
+    /// Always use the usable key with the highest weight, falling back to the next ones
+    Priority,
+}
+
+/// Settings deciding which VRF key signs each submission.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct KeyRotationConfig {
+    /// How keys are picked among the usable ones, defaults to `round_robin`
+    pub strategy: Option<RotationStrategy>,
+    /// Weight of each key, by index. Missing weights default to 1, keys with weight 0 are never used
+    pub weights: Option<Vec<u32>>,
+    /// How often, in seconds, the on-chain status of the keys is refreshed, defaults to 300
+    pub status_refresh_secs: Option<u64>,
+    /// How long, in seconds, a key is benched after its submission fails with a contract error, defaults to 600
+    pub bench_secs: Option<u64>,
+    /// Alert when fewer keys than this are usable, defaults to 1
+    pub min_usable_keys: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct KeyState {
+    /// Whether the key is active on chain, unknown until the first status refresh
+    active: Option<bool>,
+    benched_until: Option<Instant>,
+    current_weight: i64,
+}
+
+/// Whether the number of usable keys crossed the alert threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAlert {
+    Low(usize),
+    Recovered(usize),
+}
+
+/// Runtime key selection state of a worker on a single network.
+#[derive(Debug, Clone)]
+pub struct KeyRotation {
+    config: KeyRotationConfig,
+    keys: Vec<KeyState>,
+    next: usize,
+    last_refresh: Option<Instant>,
+    alerted: bool,
+}
+
+impl KeyRotation {
+    pub fn new(config: KeyRotationConfig, num_keys: usize, next: usize) -> Self {
+        Self {
+            config,
+            keys: vec![KeyState::default(); num_keys],
+            next: next % num_keys.max(1),
+            last_refresh: None,
+            alerted: false,
+        }
+    }
+
+    /// Index of the key the next round-robin selection starts from.
+    pub fn next(&self) -> usize {
+        self.next
+    }
+
+    fn weight(&self, index: usize) -> u32 {
+        self.config
+            .weights
+            .as_ref()
+            .and_then(|weights| weights.get(index).copied())
+            .unwrap_or(1)
+    }
+
+    pub fn needs_refresh(&self, now: Instant) -> bool {
+        let interval = Duration::from_secs(self.config.status_refresh_secs.unwrap_or(300));
+        self.last_refresh
+            .is_none_or(|last| now.duration_since(last) >= interval)
+    }
+
+    pub fn set_active(&mut self, index: usize, active: bool) {
+        self.keys[index].active = Some(active);
+    }
+
+    pub fn refreshed(&mut self, now: Instant) {
+        self.last_refresh = Some(now);
+    }
+
+    /// Stops using a key for a while, after it produced a contract error.
+    pub fn bench(&mut self, index: usize, now: Instant) -> Duration {
+        let duration = Duration::from_secs(self.config.bench_secs.unwrap_or(600));
+        self.keys[index].benched_until = Some(now + duration);
+        duration
+    }
+
+    pub fn is_usable(&self, index: usize, now: Instant) -> bool {
+        let key = &self.keys[index];
+        key.active != Some(false)
+            && key.benched_until.is_none_or(|until| now >= until)
+            && self.weight(index) > 0
+    }
+
+    pub fn usable(&self, now: Instant) -> usize {
+        (0..self.keys.len())
+            .filter(|i| self.is_usable(*i, now))
+            .count()
+    }
+
+    /// Picks the key to sign the next submission with, if any key is usable.
+    pub fn select(&mut self, now: Instant) -> Option<usize> {
+        let usable = (0..self.keys.len())
+            .filter(|i| self.is_usable(*i, now))
+            .collect::<Vec<_>>();
+        if usable.is_empty() {
+            return None;
+        }
+        let index = match self.config.strategy.unwrap_or_default() {
+            RotationStrategy::RoundRobin => {
+                let len = self.keys.len();
+                (0..len)
+                    .map(|offset| (self.next + offset) % len)
+                    .find(|i| usable.contains(i))?
+            }
+            RotationStrategy::Weighted => {
+                // Smooth weighted round-robin, which spreads heavier keys evenly.
+                let total = usable
+                    .iter()
+                    .map(|i| i64::from(self.weight(*i)))
+                    .sum::<i64>();
+                for i in &usable {
+                    self.keys[*i].current_weight += i64::from(self.weight(*i));
+                }
+                let index = *usable
+                    .iter()
+                    .max_by_key(|i| (self.keys[**i].current_weight, std::cmp::Reverse(**i)))?;
+                self.keys[index].current_weight -= total;
+                index
+            }
+            RotationStrategy::Priority => *usable
+                .iter()
+                .max_by_key(|i| (self.weight(**i), std::cmp::Reverse(**i)))?,
+        };
+        self.next = (index + 1) % self.keys.len();
+        Some(index)
+    }
+
+    /// Reports when the number of usable keys drops below, or recovers to, the
+    /// configured threshold. Each transition is only reported once.
+    pub fn check_threshold(&mut self, now: Instant) -> Option<KeyAlert> {
+        let usable = self.usable(now);
+        let low = usable < self.config.min_usable_keys.unwrap_or(1);
+        if low == self.alerted {
+            return None;
+        }
+        self.alerted = low;
+        Some(if low {
+            KeyAlert::Low(usable)
+        } else {
+            KeyAlert::Recovered(usable)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotation(strategy: RotationStrategy, weights: &[u32]) -> KeyRotation {
+        let config = KeyRotationConfig {
+            strategy: Some(strategy),
+            weights: Some(weights.to_vec()),
+            ..Default::default()
+        };
+        KeyRotation::new(config, weights.len(), 0)
+    }
+
+    fn picks(rotation: &mut KeyRotation, n: usize, now: Instant) -> Vec<usize> {
+        (0..n).map(|_| rotation.select(now).unwrap()).collect()
+    }
+
+    #[test]
+    fn round_robin_skips_inactive_and_benched_keys() {
+        let now = Instant::now();
+        let mut keys = rotation(RotationStrategy::RoundRobin, &[1, 1, 1, 1]);
+        keys.set_active(1, false);
+        keys.bench(2, now);
+        assert_eq!(picks(&mut keys, 3, now), vec![0, 3, 0]);
+
+        let later = now + Duration::from_secs(10 * 60);
+        assert_eq!(picks(&mut keys, 2, later), vec![2, 3]);
+    }
+
+    #[test]
+    fn weighted_and_priority_selection() {
+        let now = Instant::now();
+        let mut keys = rotation(RotationStrategy::Weighted, &[2, 1, 0]);
+        assert_eq!(picks(&mut keys, 6, now), vec![0, 1, 0, 0, 1, 0]);
+
+        let mut keys = rotation(RotationStrategy::Priority, &[1, 3, 2]);
+        assert_eq!(picks(&mut keys, 2, now), vec![1, 1]);
+        keys.bench(1, now);
+        assert_eq!(keys.select(now), Some(2));
+    }
+
+    #[test]
+    fn threshold_alerts_once_per_transition() {
+        let now = Instant::now();
+        let config = KeyRotationConfig {
+            min_usable_keys: Some(2),
+            ..Default::default()
+        };
+        let mut keys = KeyRotation::new(config, 2, 0);
+        assert_eq!(keys.check_threshold(now), None);
+        keys.bench(0, now);
+        assert_eq!(keys.check_threshold(now), Some(KeyAlert::Low(1)));
+        assert_eq!(keys.check_threshold(now), None);
+        let later = now + Duration::from_secs(10 * 60);
+        assert_eq!(keys.check_threshold(later), Some(KeyAlert::Recovered(2)));
+    }
+}
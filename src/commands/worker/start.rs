@@ -1,22 +1,39 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
-use std::str::FromStr;
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::Arc,
+    time::Instant,
+};
 
 use clap::Parser;
 use cosmrs::{tx::Gas, AccountId};
-use ecvrf_rs::{decode_hex, Proof};
-use entropy_beacon_cosmos::beacon::BEACON_BASE_GAS;
+use cosmwasm_std::Uint128;
+use ecvrf_rs::{Proof, SecretKey};
+use entropy_beacon_cosmos::{beacon::BEACON_BASE_GAS, provide::ActiveRequestInfo};
+use tokio::task::JoinSet;
 
 use crate::{
-    cosmos::{utils::mul_gas_float, wallet::Wallet},
+    cosmos::{tx::TxError, wallet::Wallet},
     utils::{
         beacon_interface::Beacon,
         config::{ConfigType, ConfigUtils},
+        vrf::{self, VerifyError},
         webhook, CLITheme,
     },
 };
 
+use super::{
+    competition::CompetitionConfig,
+    journal::{self, Journal, JournalEvent, NetworkState},
+    key_file,
+    policy::{SkipReason, SubmissionPolicy},
+    rotation::{KeyAlert, KeyRotation, KeyRotationConfig},
+    worker_config::WorkerConfig,
+};
+
 #[derive(Debug, Parser, Clone)]
 pub struct StartCommandOptions {
     /// Path to the configuration file
@@ -27,9 +44,33 @@ pub struct StartCommandOptions {
     #[clap(short, long)]
     #[clap(default_value = "false")]
     verbose: bool,
-    /// Fee granter address
+    /// Fee granter address, used for networks without a fee granter in the config file
     #[clap(long)]
     fee_granter: Option<String>,
+    /// Networks to serve (defined in config). Defaults to the default network, or the NETWORK environment variable
+    #[clap(short, long)]
+    network: Vec<String>,
+    /// Serve every network defined in the config file
+    #[clap(long, conflicts_with = "network")]
+    all_networks: bool,
+    /// Path to the journal file used to persist worker state. It is never truncated, rotate it while the worker is stopped
+    #[clap(short, long)]
+    #[clap(default_value = "journal.jsonl")]
+    journal: String,
+}
+
+/// Everything a worker needs to serve a single network.
+struct NetworkWorker {
+    name: String,
+    label: String,
+    beacon: Beacon,
+    keys: Vec<SecretKey>,
+    fee_granter: Option<AccountId>,
+    is_subsidized: bool,
+    policy: SubmissionPolicy,
+    key_rotation: KeyRotationConfig,
+    max_tx_gas: Option<u64>,
+    use_block_gas_limit: bool,
 }
 
 #[allow(clippy::too_many_lines)]
@@ -47,7 +88,7 @@ pub async fn start_cmd(options: StartCommandOptions) {
         );
         std::process::exit(1);
     });
-    let config = if let ConfigType::Worker(config) = config {
+    let mut config = if let ConfigType::Worker(config) = config {
         config
     } else {
         eprintln!(
@@ -58,32 +99,138 @@ pub async fn start_cmd(options: StartCommandOptions) {
         );
         std::process::exit(1);
     };
+    // Key file keys are used on every network, including those with keys of their own
+    if let Some(key_file) = &config.key_file {
+        let keys = key_file::unlock(key_file);
+        for network in config.networks.values_mut() {
+            if let Some(network_keys) = &mut network.registered_keys {
+                network_keys.extend(keys.iter().copied());
+            }
+        }
+        config.registered_keys.extend(keys);
+    }
 
-    if config.registered_keys.is_empty() {
+    let network_names = if options.all_networks {
+        let mut names = config.networks.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    } else if options.network.is_empty() {
+        let network_name = config.default_network.clone().unwrap_or_else(||
+            std::env::var("NETWORK").unwrap_or_else(|_|{
+                eprintln!(
+                    "{}",
+                    theme.error.apply_to("No default network set, please set the default network in the config file, or set the NETWORK environment variable")
+                );
+                std::process::exit(1);
+            })
+        );
+        network_name
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    } else {
+        options.network.clone()
+    };
+
+    if network_names.is_empty() {
         eprintln!(
             "{}",
-            theme.error.apply_to("No keys registered, please create and whitelist keys using entropycli, or add existing whitelisted keys to the config file")
+            theme
+                .error
+                .apply_to("No networks to serve, please add a network to the config file")
         );
         std::process::exit(1);
     }
 
-    let network_name = config.default_network.unwrap_or_else(||
-        std::env::var("NETWORK").unwrap_or_else(|_|{
+    let fee_granter = options
+        .fee_granter
+        .clone()
+        .or_else(|| std::env::var("FEE_GRANTER").ok());
+
+    let workers = network_names
+        .iter()
+        .map(|name| init_network_worker(name, &config, fee_granter.as_ref()))
+        .collect::<Vec<_>>();
+
+    let journal = Arc::new(Journal::open(&options.journal).unwrap_or_else(|e| {
+        eprintln!(
+            "{} {}",
+            theme.error.apply_to("Error opening journal file: "),
+            theme.error.apply_to(e.to_string())
+        );
+        std::process::exit(1);
+    }));
+
+    let mut competition = config.competition.clone().unwrap_or_default();
+    if let Ok(index) = std::env::var("REPLICA_INDEX") {
+        competition.replica_index = Some(index.parse().unwrap_or_else(|_| {
             eprintln!(
-                "{}",
-                theme.error.apply_to("No default network set, please set the default network in the config file, or set the NETWORK environment variable")
+                "{} {}",
+                theme.error.apply_to("Invalid REPLICA_INDEX: "),
+                theme.error.apply_to(&index)
             );
             std::process::exit(1);
-        })
-    );
+        }));
+    }
+
+    let webhook_url = std::env::var("WEBHOOK_URL").ok();
 
-    let network_info = config.networks.get(&network_name).unwrap_or_else(|| {
+    let mut tasks = JoinSet::new();
+    for worker in workers {
+        println!("[INFO] [{}] Starting worker", worker.label);
+        let state = journal.network_state(&worker.name).unwrap_or_else(|e| {
+            eprintln!(
+                "{} {}",
+                theme.error.apply_to("Error reading journal file: "),
+                theme.error.apply_to(e.to_string())
+            );
+            std::process::exit(1);
+        });
+        let runner = NetworkRunner {
+            keys: KeyRotation::new(
+                worker.key_rotation.clone(),
+                worker.keys.len(),
+                state.next_key,
+            ),
+            max_tx_gas: worker.max_tx_gas,
+            worker,
+            journal: journal.clone(),
+            state,
+            competition: competition.clone(),
+            not_before: HashMap::new(),
+            lost_races: 0,
+            verbose: options.verbose,
+            webhook_url: webhook_url.clone(),
+            filtered_errors: config.filtered_errors.clone(),
+        };
+        tasks.spawn(runner.run());
+    }
+
+    while let Some(res) = tasks.join_next().await {
+        if let Err(e) = res {
+            let message = format!("Worker task stopped unexpectedly: {e}");
+            eprintln!("[ERROR] {message}");
+            send_webhook_error(message, &webhook_url, &config.filtered_errors);
+        }
+    }
+}
+
+#[allow(clippy::too_many_lines)]
+fn init_network_worker(
+    network_name: &str,
+    config: &WorkerConfig,
+    fee_granter: Option<&String>,
+) -> NetworkWorker {
+    let theme = CLITheme::default();
+
+    let network_info = config.networks.get(network_name).unwrap_or_else(|| {
         eprintln!(
             "{} {}, {}",
             theme
                 .error
                 .apply_to("No network configuration found with the name"),
-            theme.error.apply_to(&network_name),
+            theme.error.apply_to(network_name),
             theme
                 .error
                 .apply_to("please add the network to the config file manually or with entropycli")
@@ -91,7 +238,17 @@ pub async fn start_cmd(options: StartCommandOptions) {
         std::process::exit(1);
     });
 
-    let gas_info = network_info.network.gas_info.clone();
+    let keys = config.keys_for(network_name).clone();
+
+    if keys.is_empty() {
+        eprintln!(
+            "{} {}, {}",
+            theme.error.apply_to("No keys registered for network"),
+            theme.error.apply_to(network_name),
+            theme.error.apply_to("please create and whitelist keys using entropycli, or add existing whitelisted keys to the config file")
+        );
+        std::process::exit(1);
+    }
 
     let beacon_address = network_info
         .network
@@ -101,7 +258,7 @@ pub async fn start_cmd(options: StartCommandOptions) {
             eprintln!(
                 "{} {} {}",
                 theme.error.apply_to("No beacon address found for network"),
-                theme.error.apply_to(&network_name),
+                theme.error.apply_to(network_name),
                 theme.error.apply_to(
                     "please add the beacon address to the config file manually or with entropycli"
                 )
@@ -131,123 +288,484 @@ pub async fn start_cmd(options: StartCommandOptions) {
         beacon_address,
     );
 
-    let webhook_url = std::env::var("WEBHOOK_URL").ok();
-
-    let fee_granter = options
+    let chain_prefix = &network_info.network.account_info.chain_prefix;
+    let fee_granter = network_info
         .fee_granter
-        .map_or(std::env::var("FEE_GRANTER").ok(), Some)
+        .as_ref()
+        .or(fee_granter)
         .map(|fee_granter| {
-            AccountId::from_str(fee_granter.as_str()).unwrap_or_else(|_| {
+            let account = AccountId::from_str(fee_granter.as_str()).unwrap_or_else(|_| {
                 eprintln!(
                     "{} {}",
                     theme.error.apply_to("Invalid fee granter address: "),
                     theme.error.apply_to(fee_granter)
                 );
                 std::process::exit(1);
-            })
+            });
+            if account.prefix() != chain_prefix {
+                eprintln!(
+                    "{} {} {} {}",
+                    theme.error.apply_to("Fee granter address"),
+                    theme.error.apply_to(fee_granter),
+                    theme.error.apply_to("is not valid for network"),
+                    theme.error.apply_to(network_name)
+                );
+                std::process::exit(1);
+            }
+            account
         });
 
-    let is_subsidized = network_info.network.subsidized_callbacks.unwrap_or(false);
+    NetworkWorker {
+        name: network_name.to_string(),
+        label: network_info
+            .label
+            .clone()
+            .unwrap_or_else(|| network_name.to_string()),
+        is_subsidized: network_info.network.subsidized_callbacks.unwrap_or(false),
+        policy: network_info
+            .policy
+            .clone()
+            .or_else(|| config.policy.clone())
+            .unwrap_or_default(),
+        key_rotation: network_info
+            .key_rotation
+            .clone()
+            .or_else(|| config.key_rotation.clone())
+            .unwrap_or_default(),
+        max_tx_gas: network_info.max_tx_gas.or(config.max_tx_gas),
+        use_block_gas_limit: network_info
+            .use_block_gas_limit
+            .or(config.use_block_gas_limit)
+            .unwrap_or(false),
+        beacon,
+        keys,
+        fee_granter,
+    }
+}
+
+/// Runtime state of a worker serving a single network.
+struct NetworkRunner {
+    worker: NetworkWorker,
+    journal: Arc<Journal>,
+    state: NetworkState,
+    keys: KeyRotation,
+    max_tx_gas: Option<u64>,
+    competition: CompetitionConfig,
+    /// When batches led by another replica may be submitted, by the id that picks their leader
+    not_before: HashMap<u128, Instant>,
+    lost_races: u64,
+    verbose: bool,
+    webhook_url: Option<String>,
+    filtered_errors: Option<Vec<String>>,
+}
 
-    let filtered_errors = config.filtered_errors;
+impl NetworkRunner {
+    fn record(&mut self, event: JournalEvent) {
+        match self.journal.append(&self.worker.name, event) {
+            Ok(record) => self.state.apply(&record),
+            Err(e) => eprintln!(
+                "[WARN] [{}] Failed to write journal entry: {e}",
+                self.worker.label
+            ),
+        }
+    }
 
-    let mut current_key = 0;
-    loop {
-        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    fn warn(&self, message: &str) {
+        let message = format!("[{}] {message}", self.worker.label);
+        eprintln!("[WARN] {message}");
+        send_webhook_error(message, &self.webhook_url, &self.filtered_errors);
+    }
 
-        let active_requests = beacon.fetch_active_requests().await;
-        if active_requests.is_err() {
-            let message = format!(
-                "Failed to fetch active requests: {}",
-                active_requests.unwrap_err()
-            );
-            eprintln!("[WARN] {}", message);
-            send_webhook_error(message, &webhook_url, &filtered_errors);
-            continue;
+    fn info(&self, message: &str) {
+        if self.verbose {
+            println!("[INFO] [{}] {message}", self.worker.label);
         }
-        let active_requests = active_requests.unwrap();
-        let requests = active_requests.requests;
+    }
+
+    async fn run(mut self) {
+        let label = self.worker.label.clone();
+        if self.keys.next() != 0 {
+            println!("[INFO] [{label}] Resuming key rotation at key {}", self.keys.next());
+        }
+
+        if self.worker.use_block_gas_limit {
+            match self.worker.beacon.network.block_max_gas().await {
+                Ok(Some(block_max_gas)) => {
+                    println!("[INFO] [{label}] Block gas limit: {block_max_gas}");
+                    self.max_tx_gas = Some(
+                        self.max_tx_gas
+                            .map_or(block_max_gas, |max| max.min(block_max_gas)),
+                    );
+                }
+                Ok(None) => println!("[INFO] [{label}] Block gas limit: unlimited"),
+                Err(e) => self.warn(&format!("Failed to fetch block gas limit: {e}")),
+            }
+        }
+
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            self.poll().await;
+        }
+    }
+
+    async fn poll(&mut self) {
+        let requests = match self.worker.beacon.fetch_active_requests().await {
+            Ok(active_requests) => active_requests.requests,
+            Err(e) => {
+                self.warn(&format!("Failed to fetch active requests: {e}"));
+                return;
+            }
+        };
+        let active = requests.iter().map(|r| r.id.u128()).collect::<HashSet<_>>();
+        self.state.retain_active(&active);
+        self.not_before.retain(|id, _| active.contains(id));
+        let now = journal::now();
+        let requests = requests
+            .into_iter()
+            .filter(|r| !self.state.is_handled(r.id.u128(), now))
+            .collect::<Vec<_>>();
         if requests.is_empty() {
-            continue;
+            return;
         }
-        let total_payout = requests
-            .iter()
-            .map(|r| r.submitted_bounty_amount.u128())
-            .sum::<u128>();
 
-        let total_callback_gas =
-            BEACON_BASE_GAS + requests.iter().map(|r| r.callback_gas_limit).sum::<u64>();
+        let new_ids = requests
+            .iter()
+            .map(|r| r.id)
+            .filter(|id| !self.state.observed.contains(&id.u128()))
+            .collect::<Vec<_>>();
+        if !new_ids.is_empty() {
+            self.record(JournalEvent::Observed {
+                request_ids: new_ids,
+            });
+        }
 
-        let total_gas_cost = mul_gas_float(total_callback_gas, gas_info.gas_price).value();
+        let height = if self.worker.policy.needs_height() {
+            match self.worker.beacon.signer.block_height().await {
+                Ok(height) => Some(u64::from(height)),
+                Err(e) => {
+                    self.warn(&format!("Failed to fetch block height: {e}"));
+                    return;
+                }
+            }
+        } else {
+            None
+        };
 
-        if !is_subsidized && total_payout < total_gas_cost.into() {
+        let selection = self.worker.policy.select(
+            requests,
+            self.worker.beacon.network.gas_info.gas_price,
+            self.worker.is_subsidized,
+            height,
+            self.max_tx_gas,
+        );
+        for (id, reason) in &selection.skipped {
+            if matches!(reason, SkipReason::GasLimit | SkipReason::TxGasLimit) {
+                self.warn(&format!("Request {id} cannot be submitted: {reason}"));
+            } else {
+                self.info(&format!("Skipping request {id}: {reason}"));
+            }
+        }
+        if selection.batches.is_empty() {
             eprintln!(
-                "[WARN] Not enough funds to pay for gas, skipping ({} < {})",
-                total_payout, total_gas_cost
+                "[WARN] [{}] No requests worth submitting, skipping {} requests",
+                self.worker.label,
+                selection.skipped.len()
             );
-            continue;
+            return;
         }
 
-        if options.verbose {
-            println!(
-                "[INFO] {} active requests, callback gas: {}, total payout: {}",
-                requests.len(),
-                total_callback_gas,
-                total_payout
-            );
+        self.info(&format!(
+            "{} active requests, gas: {}, total payout: {}",
+            selection.requests().count(),
+            selection.gas(),
+            selection.payout(),
+        ));
+        if selection.batches.len() > 1 {
+            self.info(&format!(
+                "Splitting requests into {} batches",
+                selection.batches.len()
+            ));
         }
 
-        let last_entropy = beacon.fetch_last_entropy().await;
-        if last_entropy.is_err() {
-            let message = format!(
-                "Failed to fetch last entropy: {}",
-                last_entropy.unwrap_err()
-            );
-            eprintln!("[WARN] {}", message);
-            send_webhook_error(message, &webhook_url, &filtered_errors);
-            continue;
+        for batch in selection.batches {
+            if self.awaiting_leader(&batch) {
+                continue;
+            }
+            // Each batch is chained from the entropy produced by the previous one,
+            // so a failed batch means the remaining ones must wait for the next poll.
+            if !self.submit_batch(batch).await {
+                break;
+            }
         }
+    }
 
-        if options.verbose {
-            println!(
-                "[INFO] Last entropy: {}",
-                last_entropy.as_ref().unwrap().entropy
-            );
+    /// Whether a batch led by another replica is still within its grace period. The
+    /// grace period is checked on every poll rather than waited out, so the worker
+    /// keeps polling and submitting the batches it leads in the meantime.
+    fn awaiting_leader(&mut self, batch: &[ActiveRequestInfo]) -> bool {
+        let request_ids = batch.iter().map(|r| r.id).collect::<Vec<_>>();
+        if self.competition.is_leader(&request_ids) {
+            return false;
+        }
+        let now = Instant::now();
+        let leader_id = request_ids[0].u128();
+        if !self.not_before.contains_key(&leader_id) {
+            self.info("Not the leader for this batch, waiting for the leader to submit");
+            self.not_before
+                .insert(leader_id, now + self.competition.leader_grace());
+        }
+        now < self.not_before[&leader_id]
+    }
+
+    /// Refreshes the on-chain status of every key, if it is due.
+    async fn refresh_key_status(&mut self) {
+        let now = Instant::now();
+        if !self.keys.needs_refresh(now) {
+            return;
         }
+        for (index, sk) in self.worker.keys.clone().iter().enumerate() {
+            let Ok((pk, _)) = sk.extract_public_key_and_scalar() else {
+                self.warn(&format!("Invalid key {index}, skipping its status"));
+                continue;
+            };
+            match self.worker.beacon.fetch_key_status(pk).await {
+                Ok(status) => {
+                    if !status.active {
+                        self.info(&format!("Key {index} ({pk}) is not active"));
+                    }
+                    self.keys.set_active(index, status.active);
+                }
+                Err(e) => self.warn(&format!("Failed to fetch status of key {index}: {e}")),
+            }
+        }
+        self.keys.refreshed(now);
+        self.check_usable_keys();
+    }
 
+    fn check_usable_keys(&mut self) {
+        match self.keys.check_threshold(Instant::now()) {
+            Some(KeyAlert::Low(usable)) => self.warn(&format!(
+                "Only {usable} of {} keys are usable",
+                self.worker.keys.len()
+            )),
+            Some(KeyAlert::Recovered(usable)) => println!(
+                "[INFO] [{}] {usable} of {} keys are usable again",
+                self.worker.label,
+                self.worker.keys.len()
+            ),
+            None => {}
+        }
+    }
+
+    /// Fetches the ids of the requests that are currently active on chain.
+    async fn active_ids(&self) -> Option<HashSet<u128>> {
+        match self.worker.beacon.fetch_active_requests().await {
+            Ok(active) => Some(active.requests.iter().map(|r| r.id.u128()).collect()),
+            Err(e) => {
+                self.warn(&format!("Failed to fetch active requests: {e}"));
+                None
+            }
+        }
+    }
+
+    fn lost_race(&mut self, request_ids: Vec<Uint128>, tx_hash: Option<String>) {
+        self.lost_races += 1;
+        println!(
+            "[INFO] [{}] Lost race for requests [{}], fulfilled by another worker (lost races: {})",
+            self.worker.label,
+            request_ids
+                .iter()
+                .map(std::string::ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.lost_races
+        );
+        self.record(JournalEvent::LostRace {
+            request_ids,
+            tx_hash,
+        });
+    }
+
+    /// Submits entropy for a single batch of requests, returning whether it succeeded.
+    #[allow(clippy::too_many_lines)]
+    async fn submit_batch(&mut self, requests: Vec<ActiveRequestInfo>) -> bool {
+        let label = self.worker.label.clone();
+        let mut requests = requests;
+
+        if let Some(jitter) = self.competition.jitter() {
+            tokio::time::sleep(jitter).await;
+        }
+
+        if self.competition.refetch_before_submit() {
+            let Some(active) = self.active_ids().await else {
+                return false;
+            };
+            let (still_active, fulfilled): (Vec<_>, Vec<_>) = requests
+                .into_iter()
+                .partition(|r| active.contains(&r.id.u128()));
+            if !fulfilled.is_empty() {
+                self.lost_race(fulfilled.iter().map(|r| r.id).collect(), None);
+            }
+            if still_active.is_empty() {
+                return true;
+            }
+            requests = still_active;
+        }
+
+        self.refresh_key_status().await;
+
+        let last_entropy = match self.worker.beacon.fetch_last_entropy().await {
+            Ok(last_entropy) => last_entropy,
+            Err(e) => {
+                self.warn(&format!("Failed to fetch last entropy: {e}"));
+                return false;
+            }
+        };
+
+        self.info(&format!("Last entropy: {}", last_entropy.entropy));
+
+        let sequence = match self.worker.beacon.signer.account_number_and_sequence().await {
+            Ok((_, sequence)) => sequence,
+            Err(e) => {
+                self.warn(&format!("Failed to fetch account sequence: {e}"));
+                return false;
+            }
+        };
+
+        let gas = BEACON_BASE_GAS + requests.iter().map(|r| r.callback_gas_limit).sum::<u64>();
         let request_ids = requests.iter().map(|r| r.id).collect::<Vec<_>>();
-        let last_entropy = decode_hex(last_entropy.unwrap().entropy.as_str()).unwrap();
-        let secret_key = &config.registered_keys[current_key];
-        let proof = Proof::new(secret_key, &last_entropy).unwrap();
+        let last_entropy = match hex::decode(last_entropy.entropy.as_str()) {
+            Ok(last_entropy) => last_entropy,
+            Err(e) => {
+                self.warn(&format!("Failed to decode last entropy: {e}"));
+                return false;
+            }
+        };
+        let Some(key_index) = self.keys.select(Instant::now()) else {
+            self.check_usable_keys();
+            self.warn("No usable keys, skipping submission");
+            return false;
+        };
+        let proof = match Proof::new(&self.worker.keys[key_index], &last_entropy) {
+            Ok(proof) => proof,
+            Err(e) => {
+                self.warn(&format!("Failed to generate proof with key {key_index}: {e}"));
+                return false;
+            }
+        };
+
+        // Submitting a proof the beacon rejects only wastes gas, so check it against
+        // the key and the entropy on chain right before broadcasting.
+        let current_entropy = match self.worker.beacon.fetch_last_entropy().await {
+            Ok(current_entropy) => match hex::decode(current_entropy.entropy.as_str()) {
+                Ok(current_entropy) => current_entropy,
+                Err(e) => {
+                    self.warn(&format!("Failed to decode last entropy: {e}"));
+                    return false;
+                }
+            },
+            Err(e) => {
+                self.warn(&format!("Failed to fetch last entropy: {e}"));
+                return false;
+            }
+        };
+        let public_key = match self.worker.keys[key_index].extract_public_key_and_scalar() {
+            Ok((public_key, _)) => public_key,
+            Err(e) => {
+                self.warn(&format!("Invalid key {key_index}: {e}"));
+                return false;
+            }
+        };
+        match vrf::verify_proof(&proof, Some(&public_key), Some(&current_entropy)) {
+            Ok(_) => {}
+            Err(VerifyError::Message { .. }) => {
+                self.info("Last entropy changed while generating the proof, retrying");
+                return false;
+            }
+            Err(e) => {
+                self.warn(&format!("Proof failed local verification, not submitting: {e}"));
+                return false;
+            }
+        }
         println!(
-            "[INFO] Submitting entropy with proof {}",
+            "[INFO] [{label}] Submitting entropy with proof {}",
             serde_json::to_string(&proof).unwrap()
         );
-        let res = beacon
+        self.record(JournalEvent::Submitting {
+            request_ids: request_ids.clone(),
+            key_index,
+            proof: proof.clone(),
+        });
+        let res = match self
+            .worker
+            .beacon
             .submit_entropy(
                 &proof,
-                Gas::from(total_callback_gas),
-                request_ids,
-                fee_granter.clone(),
+                Gas::from(gas),
+                request_ids.clone(),
+                self.worker.fee_granter.clone(),
             )
-            .await;
-        if res.is_err() {
-            let message = format!("Failed to submit entropy: {}", res.unwrap_err());
-            eprintln!("[WARN] {}", message);
-            send_webhook_error(message, &webhook_url, &filtered_errors);
-            continue;
-        }
-        let res = res.unwrap();
-        let message = format!("Submitted entropy with hash {}", res.txhash,);
+            .await
+        {
+            Ok(res) => res,
+            Err(e) => {
+                let (tx_hash, gas_used) = match &e {
+                    TxError::TxFailed(res) => {
+                        (Some(res.txhash.clone()), res.gas_used.parse::<u64>().ok())
+                    }
+                    _ => (None, None),
+                };
+                let (error, contract_error) = match &e {
+                    TxError::TxFailed(res) => (res.raw_log.clone(), true),
+                    _ => (e.to_string(), false),
+                };
+                // Errors only hint at a lost race, the requests must really be gone
+                let fulfilled_elsewhere = self.active_ids().await.is_some_and(|active| {
+                    request_ids.iter().all(|id| !active.contains(&id.u128()))
+                });
+                if fulfilled_elsewhere {
+                    self.lost_race(request_ids, tx_hash);
+                    return false;
+                }
+                // A race error with requests still active isn't the key's fault
+                if contract_error && !self.competition.is_race_error(&error) {
+                    let duration = self.keys.bench(key_index, Instant::now());
+                    self.warn(&format!(
+                        "Benching key {key_index} for {}s after a contract error",
+                        duration.as_secs()
+                    ));
+                    self.check_usable_keys();
+                }
+                self.record(JournalEvent::Failed {
+                    request_ids,
+                    key_index,
+                    tx_hash,
+                    gas_used,
+                    error: e.to_string(),
+                });
+                self.warn(&format!("Failed to submit entropy: {e}"));
+                return false;
+            }
+        };
+        self.record(JournalEvent::Submitted {
+            request_ids,
+            key_index,
+            tx_hash: res.txhash.clone(),
+            gas_used: res.gas_used.parse::<u64>().unwrap_or_default(),
+        });
+        let message = format!("[{label}] Submitted entropy with hash {}", res.txhash);
 
-        println!("[INFO] {}", message);
-        send_webhook(message, &webhook_url);
+        println!("[INFO] {message}");
+        send_webhook(message, &self.webhook_url);
 
-        if options.verbose {
-            println!("[INFO] Response: {:?}", res);
-        }
+        self.info(&format!("Response: {res:?}"));
 
-        current_key = (current_key + 1) % config.registered_keys.len();
+        // Make sure the next batch is signed with the sequence following this transaction.
+        if let Err(e) = self.worker.beacon.signer.wait_for_sequence(sequence + 1).await {
+            self.warn(&format!("Failed to wait for account sequence: {e}"));
+            return false;
+        }
+        true
     }
 }
 
@@ -278,4 +796,4 @@ fn send_webhook_error(message: String, url: &Option<String>, ignored: &Option<Ve
             }
         }
     });
-}
\ No newline at end of file
+}
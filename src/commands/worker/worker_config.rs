@@ -3,6 +3,9 @@ use std::collections::HashMap;
 use ecvrf_rs::SecretKey;
 use serde::{Deserialize, Serialize};
 
+use super::{
+    competition::CompetitionConfig, policy::SubmissionPolicy, rotation::KeyRotationConfig,
+};
 use crate::{
     cosmos::network::Network,
     utils::config::{Config, ConfigType},
@@ -13,14 +16,61 @@ pub struct NetworkConfiguration {
     #[serde(flatten)]
     pub network: Network,
     pub signer_mnemonic: Option<String>,
+    /// Keys used on this network, overriding the top-level `registered_keys`
+    pub registered_keys: Option<Vec<SecretKey>>,
+    /// Fee granter address used on this network
+    pub fee_granter: Option<String>,
+    /// Label used to tag log and webhook messages, defaults to the network name
+    pub label: Option<String>,
+    /// Submission policy used on this network, overriding the top-level `policy`
+    pub policy: Option<SubmissionPolicy>,
+    /// Maximum gas per submission on this network, overriding the top-level `max_tx_gas`
+    pub max_tx_gas: Option<u64>,
+    /// Whether to also bound submissions by the block gas limit of this network
+    pub use_block_gas_limit: Option<bool>,
+    /// Key selection used on this network, overriding the top-level `key_rotation`
+    pub key_rotation: Option<KeyRotationConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct WorkerConfig {
     pub registered_keys: Vec<SecretKey>,
+    /// Encrypted key file holding additional keys, used alongside `registered_keys`
+    /// and the keys of every network that has its own
+    pub key_file: Option<String>,
     pub networks: HashMap<String, NetworkConfiguration>,
     pub default_network: Option<String>,
     pub filtered_errors: Option<Vec<String>>,
+    pub policy: Option<SubmissionPolicy>,
+    /// Maximum gas per submission, larger backlogs are split into multiple transactions
+    pub max_tx_gas: Option<u64>,
+    /// Whether to also bound submissions by the block gas limit of the chain
+    pub use_block_gas_limit: Option<bool>,
+    /// Settings for running alongside other workers serving the same beacons
+    pub competition: Option<CompetitionConfig>,
+    /// Settings deciding which key signs each submission
+    pub key_rotation: Option<KeyRotationConfig>,
+}
+
+impl WorkerConfig {
+    /// Keys used on a network, falling back to the top-level `registered_keys`.
+    pub fn keys_for(&self, network: &str) -> &Vec<SecretKey> {
+        self.networks
+            .get(network)
+            .and_then(|n| n.registered_keys.as_ref())
+            .unwrap_or(&self.registered_keys)
+    }
+
+    pub fn keys_for_mut(&mut self, network: &str) -> &mut Vec<SecretKey> {
+        match self
+            .networks
+            .get_mut(network)
+            .and_then(|n| n.registered_keys.as_mut())
+        {
+            Some(keys) => keys,
+            None => &mut self.registered_keys,
+        }
+    }
 }
 
 impl Config for WorkerConfig {
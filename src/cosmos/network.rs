@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
 use bip32::DerivationPath;
 use cosmrs::{
@@ -17,6 +17,10 @@ pub struct Network {
     pub account_info: NetworkAccountInfo,
     pub gas_info: NetworkGasInfo,
     pub deployed_beacon_address: Option<String>,
+    /// Release of the deployed beacon, as of the last deploy or upgrade from a known release
+    pub deployed_beacon_version: Option<String>,
+    /// Addresses of the contracts deployed by `entropy deploy`, by name
+    pub deployed_contracts: Option<HashMap<String, String>>,
     pub subsidized_callbacks: Option<bool>,
 }
 
@@ -78,6 +82,8 @@ impl Network {
                 gas_adjustment: 1.25,
             },
             deployed_beacon_address: None,
+            deployed_beacon_version: None,
+            deployed_contracts: None,
             subsidized_callbacks: Some(false),
         }
     }
@@ -96,6 +102,8 @@ impl Network {
                 gas_adjustment: 1.25,
             },
             deployed_beacon_address: None,
+            deployed_beacon_version: None,
+            deployed_contracts: None,
             subsidized_callbacks: Some(true),
         }
     }
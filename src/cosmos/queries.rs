@@ -1,6 +1,6 @@
 use super::{network::Network, response::TxResponse, tx::TxError, wallet::Wallet};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 #[derive(Debug, Error)]
 pub enum QueryError {
@@ -12,6 +12,46 @@ pub enum QueryError {
     AccountNotFound(String),
     #[error("Error parsing response: {0}")]
     ParseError(String),
+    #[error("Timed out waiting for account sequence {0}")]
+    SequenceTimeout(u64),
+}
+
+/// On-chain information about an instantiated contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractInfo {
+    pub code_id: u64,
+    pub admin: Option<String>,
+    pub label: String,
+}
+
+/// The name and version a contract stores about itself, following cw2.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ContractVersion {
+    pub contract: String,
+    pub version: String,
+}
+
+/// Metadata of a code stored on chain.
+#[derive(Debug, Clone)]
+pub struct CodeInfo {
+    pub code_id: u64,
+    /// SHA-256 checksum of the WASM, in lowercase hex
+    pub checksum: String,
+    instantiate_permission: serde_json::Value,
+}
+
+/// A code stored on chain, with its WASM.
+#[derive(Debug, Clone)]
+pub struct StoredCode {
+    pub info: CodeInfo,
+    pub wasm: Vec<u8>,
+}
+
+impl CodeInfo {
+    /// Whether `address` is allowed to instantiate the code.
+    pub fn can_instantiate(&self, address: &str) -> bool {
+        can_instantiate(&self.instantiate_permission, address)
+    }
 }
 
 impl Wallet {
@@ -57,6 +97,18 @@ impl Wallet {
         Ok(height)
     }
 
+    /// Waits until the account sequence reported by the network reaches `sequence`.
+    pub async fn wait_for_sequence(&self, sequence: u64) -> Result<(), QueryError> {
+        for _ in 0..60 {
+            let (_, current) = self.account_number_and_sequence().await?;
+            if current >= sequence {
+                return Ok(());
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+        Err(QueryError::SequenceTimeout(sequence))
+    }
+
     pub async fn wait_for_hash(&self, tx_hash: String) -> Result<TxResponse, TxError> {
         for _ in 0..60 {
             let res = self
@@ -76,7 +128,7 @@ impl Wallet {
                 .map_err(|e| QueryError::ParseError(e.to_string()))?;
 
             if res.code != 0 {
-                return Err(TxError::TxFailed(res));
+                return Err(TxError::TxFailed(Box::new(res)));
             }
             return Ok(res);
         }
@@ -85,6 +137,148 @@ impl Wallet {
 }
 
 impl Network {
+    /// Fetches a transaction by hash, as returned by the LCD (`tx` and `tx_response`).
+    pub async fn fetch_tx(&self, tx_hash: &str) -> Result<serde_json::Value, QueryError> {
+        let response = self.get(&format!("cosmos/tx/v1beta1/txs/{tx_hash}")).await?;
+        let json: serde_json::Value = response.json().await?;
+        if json["tx"].is_null() {
+            return Err(QueryError::ParseError(format!(
+                "Transaction {tx_hash} not found: {}",
+                json["message"].as_str().unwrap_or_default()
+            )));
+        }
+        Ok(json)
+    }
+
+    /// Fetches the maximum gas per block from the consensus parameters, `None` if unlimited.
+    pub async fn block_max_gas(&self) -> Result<Option<u64>, QueryError> {
+        let response = self.get("cosmos/consensus/v1/params").await?;
+        let json: serde_json::Value = response.json().await?;
+        let max_gas = if json["params"]["block"].is_object() {
+            json["params"]["block"]["max_gas"].clone()
+        } else {
+            // Chains without the consensus module keep block params in the baseapp subspace
+            let response = self
+                .get("cosmos/params/v1beta1/params?subspace=baseapp&key=BlockParams")
+                .await?;
+            let json: serde_json::Value = response.json().await?;
+            let value = json["param"]["value"]
+                .as_str()
+                .ok_or_else(|| QueryError::ParseError("Failed to parse block params".to_string()))?;
+            let params = serde_json::from_str::<serde_json::Value>(value)
+                .map_err(|e| QueryError::ParseError(e.to_string()))?;
+            params["max_gas"].clone()
+        };
+
+        let max_gas = max_gas
+            .as_str()
+            .map(str::parse::<i64>)
+            .or_else(|| max_gas.as_i64().map(Ok))
+            .transpose()
+            .map_err(|e| QueryError::ParseError(e.to_string()))?
+            .ok_or_else(|| QueryError::ParseError("Failed to parse block max gas".to_string()))?;
+
+        // A negative max gas means blocks are not gas limited
+        Ok(u64::try_from(max_gas).ok())
+    }
+
+    /// Fetches the code id, admin and label of the contract at `address`.
+    pub async fn fetch_contract_info(&self, address: &str) -> Result<ContractInfo, QueryError> {
+        let response = self.get(&format!("cosmwasm/wasm/v1/contract/{address}")).await?;
+        let json: serde_json::Value = response.json().await?;
+        let info = &json["contract_info"];
+        let code_id = info["code_id"]
+            .as_str()
+            .and_then(|s| s.parse::<u64>().ok())
+            .ok_or_else(|| {
+                QueryError::ParseError(format!(
+                    "Contract {address} not found: {}",
+                    json["message"].as_str().unwrap_or_default()
+                ))
+            })?;
+        Ok(ContractInfo {
+            code_id,
+            admin: info["admin"]
+                .as_str()
+                .filter(|admin| !admin.is_empty())
+                .map(ToString::to_string),
+            label: info["label"].as_str().unwrap_or_default().to_string(),
+        })
+    }
+
+    /// Fetches the cw2 version of the contract at `address`, `None` if it doesn't store one.
+    pub async fn fetch_contract_version(
+        &self,
+        address: &str,
+    ) -> Result<Option<ContractVersion>, QueryError> {
+        // Raw query of the `contract_info` key, base64 encoded and escaped
+        let key = base64::encode("contract_info").replace('=', "%3D");
+        let response = self
+            .get(&format!("cosmwasm/wasm/v1/contract/{address}/raw/{key}"))
+            .await?;
+        let json: serde_json::Value = response.json().await?;
+        let Some(data) = json["data"].as_str().filter(|data| !data.is_empty()) else {
+            return Ok(None);
+        };
+        let data = base64::decode(data).map_err(|e| QueryError::ParseError(e.to_string()))?;
+        serde_json::from_slice(&data)
+            .map(Some)
+            .map_err(|e| QueryError::ParseError(e.to_string()))
+    }
+
+    /// Fetches the code with the given id, along with its WASM.
+    pub async fn fetch_code(&self, code_id: u64) -> Result<StoredCode, QueryError> {
+        let response = self.get(&format!("cosmwasm/wasm/v1/code/{code_id}")).await?;
+        let json: serde_json::Value = response.json().await?;
+        let info = code_info(code_id, &json, &json["code_info"], "data_hash")?;
+        let wasm = base64::decode(json["data"].as_str().unwrap_or_default())
+            .map_err(|e| QueryError::ParseError(e.to_string()))?;
+        Ok(StoredCode { info, wasm })
+    }
+
+    /// Fetches the metadata of the code with the given id, without downloading its WASM.
+    pub async fn fetch_code_info(&self, code_id: u64) -> Result<CodeInfo, QueryError> {
+        let response = self.get(&format!("cosmwasm/wasm/v1/code-info/{code_id}")).await?;
+        // Only wasmd 0.50 and later serve code info on its own
+        if matches!(
+            response.status(),
+            reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::NOT_IMPLEMENTED
+        ) {
+            return self.fetch_code(code_id).await.map(|code| code.info);
+        }
+        let json: serde_json::Value = response.json().await?;
+        code_info(code_id, &json, &json, "checksum")
+    }
+
+    /// Finds the newest stored code with the given SHA-256 checksum (hex) that
+    /// `instantiator` is allowed to instantiate.
+    pub async fn find_code(&self, checksum: &str, instantiator: &str) -> Result<Option<u64>, QueryError> {
+        let mut next_key: Option<String> = None;
+        loop {
+            // Pagination keys are base64, which has to be escaped in the query string
+            let key = next_key.as_deref().unwrap_or_default();
+            let key = key.replace('+', "%2B").replace('/', "%2F").replace('=', "%3D");
+            let path = format!(
+                "cosmwasm/wasm/v1/code?pagination.reverse=true&pagination.limit=100&pagination.key={key}"
+            );
+            let response = self.get(&path).await?;
+            let json: serde_json::Value = response.json().await?;
+            let codes = json["code_infos"]
+                .as_array()
+                .ok_or_else(|| QueryError::ParseError("Failed to parse code infos".to_string()))?;
+            if let Some(code_id) = codes
+                .iter()
+                .find_map(|code| matching_code(code, checksum, instantiator))
+            {
+                return Ok(Some(code_id));
+            }
+            match json["pagination"]["next_key"].as_str() {
+                Some(key) if !key.is_empty() => next_key = Some(key.to_string()),
+                _ => return Ok(None),
+            }
+        }
+    }
+
     pub async fn query(
         &self,
         address: String,
@@ -110,3 +304,78 @@ impl Network {
         Ok(json["data"].clone())
     }
 }
+
+/// Reads the metadata of a code from an LCD response, where `info` holds the
+/// checksum under `checksum_key`.
+fn code_info(
+    code_id: u64,
+    json: &serde_json::Value,
+    info: &serde_json::Value,
+    checksum_key: &str,
+) -> Result<CodeInfo, QueryError> {
+    let checksum = info[checksum_key].as_str().ok_or_else(|| {
+        QueryError::ParseError(format!(
+            "Code {code_id} not found: {}",
+            json["message"].as_str().unwrap_or_default()
+        ))
+    })?;
+    Ok(CodeInfo {
+        code_id,
+        checksum: checksum.to_lowercase(),
+        instantiate_permission: info["instantiate_permission"].clone(),
+    })
+}
+
+/// The id of a code listed by the LCD, if it has the given checksum and can be
+/// instantiated by `instantiator`.
+fn matching_code(code: &serde_json::Value, checksum: &str, instantiator: &str) -> Option<u64> {
+    if !code["data_hash"]
+        .as_str()?
+        .eq_ignore_ascii_case(checksum)
+    {
+        return None;
+    }
+    if !can_instantiate(&code["instantiate_permission"], instantiator) {
+        return None;
+    }
+    code["code_id"].as_str()?.parse().ok()
+}
+
+/// Whether an instantiate permission, as returned by the LCD, allows `instantiator`.
+fn can_instantiate(permission: &serde_json::Value, instantiator: &str) -> bool {
+    match permission["permission"].as_str() {
+        None | Some("Everybody" | "ACCESS_TYPE_EVERYBODY") => true,
+        Some("OnlyAddress" | "ACCESS_TYPE_ONLY_ADDRESS") => permission["address"] == instantiator,
+        Some("AnyOfAddresses" | "ACCESS_TYPE_ANY_OF_ADDRESSES") => permission["addresses"]
+            .as_array()
+            .is_some_and(|addresses| addresses.iter().any(|a| a == instantiator)),
+        Some(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_match_checksum_and_permission() {
+        let code = |permission: serde_json::Value| {
+            serde_json::json!({
+                "code_id": "7",
+                "creator": "terra1creator",
+                "data_hash": "ABCDEF",
+                "instantiate_permission": permission,
+            })
+        };
+        let everybody = code(serde_json::json!({"permission": "Everybody", "address": ""}));
+        assert_eq!(matching_code(&everybody, "abcdef", "terra1me"), Some(7));
+        assert_eq!(matching_code(&everybody, "012345", "terra1me"), None);
+
+        let only = code(serde_json::json!({"permission": "OnlyAddress", "address": "terra1me"}));
+        assert_eq!(matching_code(&only, "abcdef", "terra1me"), Some(7));
+        assert_eq!(matching_code(&only, "abcdef", "terra1other"), None);
+
+        let nobody = code(serde_json::json!({"permission": "Nobody"}));
+        assert_eq!(matching_code(&nobody, "abcdef", "terra1me"), None);
+    }
+}
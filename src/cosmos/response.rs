@@ -5,12 +5,17 @@ use std::collections::HashMap;
 use super::tx::TxError;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "serialization::RawEvent")]
 pub struct Event {
     #[serde(rename = "type")]
     pub type_: String,
     #[serde(serialize_with = "serialization::serialize_attributes")]
-    #[serde(deserialize_with = "serialization::deserialize_attributes")]
     pub attributes: HashMap<String, String>,
+    /// Attributes in the order they were emitted, keeping repeated keys. Events
+    /// of the same type are merged in logs, so a single `wasm` event can hold the
+    /// attributes of several contracts.
+    #[serde(skip)]
+    pub ordered_attributes: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +34,8 @@ pub struct TxResponse {
     pub gas_used: String,
     pub height: String,
     pub info: String,
+    #[serde(default)]
+    pub raw_log: String,
     pub txhash: String,
     pub logs: Vec<TxLogEntry>,
     pub timestamp: String,
@@ -59,23 +66,34 @@ mod serialization {
         seq.end()
     }
 
-    pub(crate) fn deserialize_attributes<'de, D>(
-        deserializer: D,
-    ) -> Result<HashMap<String, String>, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        // Deserialize each kv in the list from {"key": k, "value": v} to HashMap
-        #[derive(Deserialize)]
-        struct Attr {
-            key: String,
-            value: String,
+    #[derive(Deserialize)]
+    struct Attr {
+        key: String,
+        value: String,
+    }
+
+    #[derive(Deserialize)]
+    pub(crate) struct RawEvent {
+        #[serde(rename = "type")]
+        type_: String,
+        attributes: Vec<Attr>,
+    }
+
+    impl From<RawEvent> for super::Event {
+        fn from(raw: RawEvent) -> Self {
+            // Deserialize each kv in the list from {"key": k, "value": v}, keeping both
+            // a HashMap for lookups and the original order
+            let ordered_attributes = raw
+                .attributes
+                .into_iter()
+                .map(|attr| (attr.key, attr.value))
+                .collect::<Vec<_>>();
+            Self {
+                type_: raw.type_,
+                attributes: ordered_attributes.iter().cloned().collect::<HashMap<_, _>>(),
+                ordered_attributes,
+            }
         }
-        let attrs = Vec::<Attr>::deserialize(deserializer)?;
-        Ok(attrs
-            .into_iter()
-            .map(|attr| (attr.key, attr.value))
-            .collect::<HashMap<_, _>>())
     }
 }
 
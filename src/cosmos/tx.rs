@@ -22,7 +22,7 @@ pub enum TxError {
     #[error("Out of gas")]
     OutOfGas,
     #[error("Transaction failed {:?}", .0)]
-    TxFailed(TxResponse),
+    TxFailed(Box<TxResponse>),
     #[error("Timeout while waiting for transaction to be included in a block")]
     Timeout,
 }
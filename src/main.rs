@@ -7,6 +7,7 @@ mod utils;
 use clap::{Parser, Subcommand};
 use commands::{
     beacon::{beacon_cmd, BeaconCommandOptions},
+    deploy::{deploy_cmd, DeployCommandOptions},
     network::network_cmd,
     worker::{worker_cmd, WorkerCommandOptions},
 };
@@ -29,6 +30,8 @@ pub enum Command {
     Network(NetworkCommandOptions),
     #[clap(about = "Manage local beacon projects")]
     Beacon(BeaconCommandOptions),
+    #[clap(about = "Deploy the contracts of a project")]
+    Deploy(DeployCommandOptions),
     #[clap(about = "Manage worker deployments")]
     Worker(WorkerCommandOptions),
 }
@@ -39,6 +42,7 @@ async fn main() {
     match args.command {
         Command::Network(options) => network_cmd(options),
         Command::Beacon(options) => beacon_cmd(options).await,
+        Command::Deploy(options) => deploy_cmd(options).await,
         Command::Worker(options) => worker_cmd(options).await,
     }
 }
@@ -2,15 +2,21 @@ use std::str::FromStr;
 
 use cosmrs::cosmwasm::MsgExecuteContract;
 use cosmrs::tx::Gas;
-use cosmrs::AccountId;
-use cosmwasm_std::Uint128;
+use cosmrs::{AccountId, Coin, Denom};
+use cosmwasm_std::{Decimal, Uint128};
 use ecvrf_rs::{Proof, PublicKey, SecretKey};
+use entropy_beacon_cosmos::beacon::{
+    CalculateFeeQuery, CalculateFeeResponse, RequestEntropyMsg, UpdateConfigMsg,
+};
 use entropy_beacon_cosmos::msg::{ExecuteMsg as BeaconExecuteMsg, QueryMsg as BeaconQueryMsg};
 use entropy_beacon_cosmos::provide::{
-    ActiveRequestsQuery, ActiveRequestsResponse, LastEntropyQuery, LastEntropyResponse,
-    SubmitEntropyMsg, MAX_PAGINATION_LIMIT,
+    ActiveRequestsQuery, ActiveRequestsResponse, BeaconConfigQuery, BeaconConfigResponse,
+    KeyStatusQuery, KeyStatusResponse, LastEntropyQuery, LastEntropyResponse, ReclaimDepositMsg,
+    SubmitEntropyMsg, WhitelistPublicKeyMsg, MAX_PAGINATION_LIMIT,
 };
 
+use serde::de::DeserializeOwned;
+
 use crate::cosmos::response::TxResponse;
 use crate::cosmos::tx::TxError;
 use crate::cosmos::{network::Network, queries::QueryError, wallet::Wallet};
@@ -25,7 +31,6 @@ pub fn test_pk() -> PublicKey {
     PublicKey::from_bytes(&bytes)
 }
 
-#[allow(dead_code)]
 pub fn test_sk() -> SecretKey {
     //9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60 as bytes
     let bytes: [u8; 32] = [
@@ -35,6 +40,95 @@ pub fn test_sk() -> SecretKey {
     SecretKey::from_slice(&bytes)
 }
 
+/// Fetches every active request of the beacon at `address`, across all pages.
+pub async fn fetch_active_requests(
+    network: &Network,
+    address: &str,
+) -> Result<ActiveRequestsResponse, QueryError> {
+    let mut requests = vec![];
+    let mut start_after = None;
+    loop {
+        let response = network
+            .query(
+                address.to_string(),
+                BeaconQueryMsg::ActiveRequests(ActiveRequestsQuery {
+                    start_after,
+                    limit: Some(MAX_PAGINATION_LIMIT),
+                }),
+            )
+            .await?;
+
+        let response = serde_json::from_value::<ActiveRequestsResponse>(response)
+            .map_err(|e| QueryError::ParseError(e.to_string()))?;
+        requests.extend(response.requests.clone());
+        if response.requests.len() < MAX_PAGINATION_LIMIT.try_into().unwrap() {
+            break;
+        }
+        start_after = Some(response.requests.last().unwrap().id);
+    }
+
+    Ok(ActiveRequestsResponse { requests })
+}
+
+async fn query_beacon<T: DeserializeOwned>(
+    network: &Network,
+    address: &str,
+    msg: BeaconQueryMsg,
+) -> Result<T, QueryError> {
+    serde_json::from_value::<T>(network.query(address.to_string(), msg).await?)
+        .map_err(|e| QueryError::ParseError(e.to_string()))
+}
+
+pub async fn fetch_last_entropy(
+    network: &Network,
+    address: &str,
+) -> Result<LastEntropyResponse, QueryError> {
+    query_beacon(
+        network,
+        address,
+        BeaconQueryMsg::LastEntropy(LastEntropyQuery {}),
+    )
+    .await
+}
+
+pub async fn fetch_config(
+    network: &Network,
+    address: &str,
+) -> Result<BeaconConfigResponse, QueryError> {
+    query_beacon(
+        network,
+        address,
+        BeaconQueryMsg::BeaconConfig(BeaconConfigQuery {}),
+    )
+    .await
+}
+
+pub async fn calculate_fee(
+    network: &Network,
+    address: &str,
+    callback_gas_limit: u64,
+) -> Result<CalculateFeeResponse, QueryError> {
+    query_beacon(
+        network,
+        address,
+        BeaconQueryMsg::CalculateFee(CalculateFeeQuery { callback_gas_limit }),
+    )
+    .await
+}
+
+pub async fn fetch_key_status(
+    network: &Network,
+    address: &str,
+    public_key: PublicKey,
+) -> Result<KeyStatusResponse, QueryError> {
+    query_beacon(
+        network,
+        address,
+        BeaconQueryMsg::KeyStatus(KeyStatusQuery { public_key }),
+    )
+    .await
+}
+
 pub struct Beacon {
     pub network: Network,
     pub signer: Wallet,
@@ -51,42 +145,103 @@ impl Beacon {
     }
 
     pub async fn fetch_active_requests(&self) -> Result<ActiveRequestsResponse, QueryError> {
-        let mut requests = vec![];
-        let mut start_after = None;
-        loop {
-            let response = self
-                .network
-                .query(
-                    self.address.clone(),
-                    BeaconQueryMsg::ActiveRequests(ActiveRequestsQuery {
-                        start_after,
-                        limit: Some(MAX_PAGINATION_LIMIT),
-                    }),
-                )
-                .await?;
-
-            let response = serde_json::from_value::<ActiveRequestsResponse>(response)
-                .map_err(|e| QueryError::ParseError(e.to_string()))?;
-            requests.extend(response.requests.clone());
-            if response.requests.len() < MAX_PAGINATION_LIMIT.try_into().unwrap() {
-                break;
-            }
-            start_after = Some(response.requests.last().unwrap().id);
-        }
-
-        Ok(ActiveRequestsResponse { requests })
+        fetch_active_requests(&self.network, &self.address).await
     }
 
     pub async fn fetch_last_entropy(&self) -> Result<LastEntropyResponse, QueryError> {
-        serde_json::from_value::<LastEntropyResponse>(
-            self.network
-                .query(
-                    self.address.clone(),
-                    BeaconQueryMsg::LastEntropy(LastEntropyQuery {}),
-                )
-                .await?,
+        fetch_last_entropy(&self.network, &self.address).await
+    }
+
+    pub async fn fetch_config(&self) -> Result<BeaconConfigResponse, QueryError> {
+        fetch_config(&self.network, &self.address).await
+    }
+
+    pub async fn calculate_fee(
+        &self,
+        callback_gas_limit: u64,
+    ) -> Result<CalculateFeeResponse, QueryError> {
+        calculate_fee(&self.network, &self.address, callback_gas_limit).await
+    }
+
+    pub async fn fetch_key_status(
+        &self,
+        public_key: PublicKey,
+    ) -> Result<KeyStatusResponse, QueryError> {
+        fetch_key_status(&self.network, &self.address, public_key).await
+    }
+
+    pub async fn whitelist_key(
+        &self,
+        public_key: PublicKey,
+        deposit: Uint128,
+        denom: &str,
+    ) -> Result<TxResponse, TxError> {
+        let funds = if deposit.is_zero() {
+            vec![]
+        } else {
+            vec![Coin {
+                denom: Denom::from_str(denom).map_err(|e| TxError::Parse(e.to_string()))?,
+                amount: deposit.u128(),
+            }]
+        };
+        self.execute(
+            &BeaconExecuteMsg::WhitelistPublicKey(WhitelistPublicKeyMsg { public_key }),
+            funds,
         )
-        .map_err(|e| QueryError::ParseError(e.to_string()))
+        .await
+    }
+
+    pub async fn reclaim_deposit(&self, public_key: PublicKey) -> Result<TxResponse, TxError> {
+        self.execute(
+            &BeaconExecuteMsg::ReclaimDeposit(ReclaimDepositMsg { public_key }),
+            vec![],
+        )
+        .await
+    }
+
+    pub async fn request_entropy(
+        &self,
+        request: RequestEntropyMsg,
+        fee: u64,
+        denom: &str,
+    ) -> Result<TxResponse, TxError> {
+        let funds = if fee == 0 {
+            vec![]
+        } else {
+            vec![Coin {
+                denom: Denom::from_str(denom).map_err(|e| TxError::Parse(e.to_string()))?,
+                amount: u128::from(fee),
+            }]
+        };
+        self.execute(&BeaconExecuteMsg::RequestEntropy(request), funds)
+            .await
+    }
+
+    pub async fn update_config(&self, msg: UpdateConfigMsg) -> Result<TxResponse, TxError> {
+        self.execute(&BeaconExecuteMsg::UpdateConfig(msg), vec![])
+            .await
+    }
+
+    pub async fn update_gas_price(&self, gas_price: Decimal) -> Result<TxResponse, TxError> {
+        self.execute(&BeaconExecuteMsg::UpdateGasPrice(gas_price), vec![])
+            .await
+    }
+
+    async fn execute(
+        &self,
+        msg: &BeaconExecuteMsg,
+        funds: Vec<Coin>,
+    ) -> Result<TxResponse, TxError> {
+        let msg = MsgExecuteContract {
+            sender: self.signer.address.clone(),
+            contract: AccountId::from_str(&self.address)
+                .map_err(|e| TxError::Parse(e.to_string()))?,
+            msg: serde_json::to_string(msg).unwrap().into_bytes(),
+            funds,
+        };
+
+        let hash = self.signer.broadcast_msg(msg, None, None).await?;
+        self.signer.wait_for_hash(hash).await
     }
 
     pub async fn submit_entropy(
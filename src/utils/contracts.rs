@@ -0,0 +1,232 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Placeholders that are always available, besides `{{<contract>_address}}`.
+pub const BEACON_ADDRESS: &str = "beacon_address";
+pub const WALLET_ADDRESS: &str = "wallet_address";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ContractError {
+    #[error("Contract {0} is not defined in the config file")]
+    UnknownContract(String),
+    #[error("Contract name {0} is reserved")]
+    ReservedName(String),
+    #[error("Contracts {0} depend on each other")]
+    Cycle(String),
+    #[error("No value for {{{{{0}}}}}, deploy the contract or beacon it refers to first")]
+    MissingValue(String),
+}
+
+/// A contract deployed by `entropy deploy`, as defined in the project config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractConfig {
+    /// Path to the WASM file of the contract
+    pub wasm: String,
+    /// Instantiate message, where `{{beacon_address}}`, `{{wallet_address}}` and
+    /// `{{<contract>_address}}` are replaced with the corresponding addresses
+    pub instantiate_msg: serde_json::Value,
+    /// Label of the contract, defaults to its name
+    pub label: Option<String>,
+    /// Contracts to deploy before this one, on top of those referenced in the instantiate message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+impl ContractConfig {
+    /// Names of the contracts this contract must be deployed after.
+    pub fn dependencies(&self) -> BTreeSet<String> {
+        let mut placeholders = BTreeSet::new();
+        collect_placeholders(&self.instantiate_msg, &mut placeholders);
+        placeholders
+            .iter()
+            .filter(|p| *p != BEACON_ADDRESS && *p != WALLET_ADDRESS)
+            .filter_map(|p| p.strip_suffix("_address"))
+            .map(ToString::to_string)
+            .chain(self.depends_on.iter().cloned())
+            .collect()
+    }
+}
+
+fn collect_placeholders(value: &serde_json::Value, placeholders: &mut BTreeSet<String>) {
+    match value {
+        serde_json::Value::String(s) => {
+            let mut rest = s.as_str();
+            while let Some((_, after)) = rest.split_once("{{") {
+                let Some((name, after)) = after.split_once("}}") else {
+                    break;
+                };
+                placeholders.insert(name.to_string());
+                rest = after;
+            }
+        }
+        serde_json::Value::Array(values) => {
+            for v in values {
+                collect_placeholders(v, placeholders);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for v in map.values() {
+                collect_placeholders(v, placeholders);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Orders the contracts named in `only` (or all contracts) so that each one is
+/// deployed after the contracts it depends on. Dependencies outside of `only`
+/// are expected to be deployed already.
+pub fn deploy_order(
+    contracts: &HashMap<String, ContractConfig>,
+    only: &[String],
+) -> Result<Vec<String>, ContractError> {
+    for name in contracts.keys() {
+        if name == "beacon" || name == "wallet" {
+            return Err(ContractError::ReservedName(name.clone()));
+        }
+    }
+    let mut pending = if only.is_empty() {
+        contracts.keys().cloned().collect::<BTreeSet<_>>()
+    } else {
+        only.iter().cloned().collect()
+    };
+    let mut dependencies = HashMap::new();
+    for name in &pending {
+        let contract = contracts
+            .get(name)
+            .ok_or_else(|| ContractError::UnknownContract(name.clone()))?;
+        let deps = contract.dependencies();
+        if let Some(dep) = deps.iter().find(|dep| !contracts.contains_key(*dep)) {
+            return Err(ContractError::UnknownContract(dep.clone()));
+        }
+        dependencies.insert(name.clone(), deps);
+    }
+
+    let mut order = vec![];
+    while !pending.is_empty() {
+        let ready = pending
+            .iter()
+            .filter(|name| dependencies[*name].iter().all(|dep| !pending.contains(dep)))
+            .cloned()
+            .collect::<Vec<_>>();
+        if ready.is_empty() {
+            return Err(ContractError::Cycle(
+                pending.into_iter().collect::<Vec<_>>().join(", "),
+            ));
+        }
+        for name in ready {
+            pending.remove(&name);
+            order.push(name);
+        }
+    }
+    Ok(order)
+}
+
+/// Replaces the placeholders in `template` with their `values`.
+pub fn render(
+    template: &serde_json::Value,
+    values: &HashMap<String, String>,
+) -> Result<serde_json::Value, ContractError> {
+    let mut placeholders = BTreeSet::new();
+    collect_placeholders(template, &mut placeholders);
+    if let Some(missing) = placeholders.iter().find(|p| !values.contains_key(*p)) {
+        return Err(ContractError::MissingValue(missing.clone()));
+    }
+    Ok(replace(template, values))
+}
+
+fn replace(value: &serde_json::Value, values: &HashMap<String, String>) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => {
+            let mut s = s.clone();
+            for (name, value) in values {
+                s = s.replace(&format!("{{{{{name}}}}}"), value);
+            }
+            serde_json::Value::String(s)
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(|v| replace(v, values)).collect())
+        }
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), replace(v, values)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(instantiate_msg: serde_json::Value, depends_on: &[&str]) -> ContractConfig {
+        ContractConfig {
+            wasm: "contract.wasm".to_string(),
+            instantiate_msg,
+            label: None,
+            depends_on: depends_on.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn contracts_are_ordered_and_rendered() {
+        let contracts = HashMap::from([
+            (
+                "game".to_string(),
+                contract(
+                    serde_json::json!({
+                        "entropy_beacon_addr": "{{beacon_address}}",
+                        "token": "{{token_address}}",
+                    }),
+                    &[],
+                ),
+            ),
+            (
+                "token".to_string(),
+                contract(serde_json::json!({ "minter": "{{wallet_address}}" }), &[]),
+            ),
+            (
+                "frontend".to_string(),
+                contract(serde_json::json!({}), &["game"]),
+            ),
+        ]);
+        assert_eq!(
+            deploy_order(&contracts, &[]).unwrap(),
+            vec!["token", "game", "frontend"]
+        );
+        assert_eq!(
+            deploy_order(&contracts, &["game".to_string()]).unwrap(),
+            vec!["game"]
+        );
+        assert_eq!(
+            deploy_order(&contracts, &["other".to_string()]),
+            Err(ContractError::UnknownContract("other".to_string()))
+        );
+
+        let mut cyclic = contracts.clone();
+        cyclic.get_mut("token").unwrap().depends_on = vec!["frontend".to_string()];
+        assert!(matches!(
+            deploy_order(&cyclic, &[]),
+            Err(ContractError::Cycle(_))
+        ));
+
+        let values = HashMap::from([
+            (BEACON_ADDRESS.to_string(), "terra1beacon".to_string()),
+            ("token_address".to_string(), "terra1token".to_string()),
+        ]);
+        assert_eq!(
+            render(&contracts["game"].instantiate_msg, &values).unwrap(),
+            serde_json::json!({
+                "entropy_beacon_addr": "terra1beacon",
+                "token": "terra1token",
+            })
+        );
+        assert_eq!(
+            render(&contracts["token"].instantiate_msg, &values),
+            Err(ContractError::MissingValue(WALLET_ADDRESS.to_string()))
+        );
+    }
+}
@@ -1,128 +1,280 @@
-use std::{path::PathBuf, str::FromStr, time::Duration};
+use std::{path::PathBuf, time::Duration};
 
 use cosmrs::cosmwasm::{
     AccessConfig, AccessType, MsgInstantiateContract, MsgStoreCode, MsgStoreCodeResponse,
 };
-use cosmwasm_std::{Decimal, Uint128};
 use indicatif::ProgressBar;
+use sha2::{Digest, Sha256};
 
 use crate::{
-    commands::beacon::project_config::ProjectConfig,
-    cosmos::wallet::Wallet,
-    utils::wasm_fetch::{download_file, fetch_release_url},
+    commands::beacon::{
+        instantiate::InstantiateOptions,
+        project_config::{load_project, ProjectConfig},
+    },
+    cosmos::{network::Network, queries::CodeInfo, wallet::Wallet},
+    utils::wasm_cache::WasmCache,
+    utils::wasm_fetch::{download_release, fetch_release},
     utils::CLITheme,
 };
 
-use entropy_beacon_cosmos::{beacon::BEACON_BASE_GAS, msg::InstantiateMsg};
-
-#[allow(clippy::too_many_lines)]
+/// Deploys a beacon with the network and wallet of the project config at
+/// `config_path`, and returns the config updated with the deployed beacon.
 pub async fn deploy_beacon(
-    network: Option<String>,
+    config_path: &str,
+    network_name: Option<String>,
     wallet: Option<String>,
     wasm_file: Option<impl Into<PathBuf>>,
-    config: &mut ProjectConfig,
-) {
+    code_id: Option<u64>,
+    beacon_version: Option<String>,
+    instantiate: &InstantiateOptions,
+) -> ProjectConfig {
     let theme = CLITheme::default();
-    let (network_name, network) = match config.get_network(&network) {
-        Ok((network_name, Some(network))) => (network_name, network),
-        Ok((name, None)) => {
-            println!(
-                "{} {} {}",
-                theme.error.apply_to("Network"),
-                theme.highlight.apply_to(name),
-                theme.error.apply_to("not found in config file.")
-            );
-            std::process::exit(1);
-        }
-        Err(_) => {
-            println!(
-                "{}",
-                theme.error.apply_to("No network specified. Please specify a network with the --network flag or set a default network in the config file.")
-            );
-            std::process::exit(1);
-        }
-    };
-
-    let wallet_name = wallet.or_else(|| config.default_wallet.clone()).unwrap_or_else(|| {
-            println!(
-                "{}",
-                theme.error.apply_to("No wallet specified. Please specify a wallet with the --wallet flag or set a default wallet in the config file.")
-            );
-            std::process::exit(1);
-        });
-
-    let wallet = config
-        .wallets
-        .as_ref()
-        .and_then(|wallets| wallets.get(&wallet_name));
-
-    let wallet = match wallet {
-        Some(Some(mnemonic)) => Wallet::new(mnemonic.clone(), network.clone()),
-        Some(None) => {
-            let mnemonic = std::env::var("MNEMONIC").unwrap_or_else(|_| {
-                println!(
-                    "{} {} {}",
-                    theme.error.apply_to("Mnemonic for wallet"),
-                    theme.highlight.apply_to(&wallet_name),
-                    theme
-                        .error
-                        .apply_to("not found in config file or MNEMONIC environment variable.")
-                );
-                std::process::exit(1);
-            });
-            Wallet::new(mnemonic, network.clone())
-        }
-        None => {
-            println!(
-                "{} {} {}",
-                theme.error.apply_to("Wallet"),
-                theme.highlight.apply_to(&wallet_name),
-                theme.error.apply_to("not found in config file.")
-            );
-            std::process::exit(1);
-        }
-    }
-    .unwrap_or_else(|e| {
+    let (mut config, network, wallet) = load_project(config_path, network_name.as_ref(), wallet);
+    let (instantiate_msg, label) = instantiate.resolve(&network).unwrap_or_else(|e| {
         println!(
             "{} {}",
-            theme.error.apply_to("Error creating wallet:"),
+            theme.error.apply_to("Invalid instantiate parameters:"),
             theme.highlight.apply_to(e)
         );
         std::process::exit(1);
     });
+
     let pb = ProgressBar::new(1);
     pb.enable_steady_tick(Duration::from_millis(80));
     pb.set_style(CLITheme::spinner());
-    let wasm_file = if let Some(wasm_file) = wasm_file {
-        wasm_file.into()
+    let beacon_version = beacon_version.or_else(|| config.beacon_version.clone());
+    let (code_id, deployed_version) = match code_id {
+        Some(code_id) => {
+            pb.set_message(format!("Fetching code {code_id}..."));
+            let code = check_code(&pb, &network, &wallet, code_id).await;
+            (code_id, known_release(&code.checksum))
+        }
+        None => {
+            store_beacon(
+                &pb,
+                &network,
+                &wallet,
+                wasm_file,
+                beacon_version.as_deref(),
+                config.pinned_checksum(beacon_version.as_deref()),
+            )
+            .await
+        }
+    };
+
+    pb.set_message(if instantiate_msg.test_mode {
+        "Instantiating beacon contract in test mode..."
     } else {
-        pb.set_message("Fetching latest release...");
-        let wasm_download_url = fetch_release_url().await.unwrap_or_else(|err| {
+        "Instantiating beacon contract..."
+    });
+
+    let deployed_address = instantiate_contract(
+        &pb,
+        &wallet,
+        code_id,
+        label,
+        serde_json::to_vec(&instantiate_msg).unwrap(),
+        "beacon",
+    )
+    .await;
+
+    pb.set_style(CLITheme::success_spinner());
+    pb.set_prefix("✓");
+    pb.finish_with_message(format!(
+        "{} {}\n",
+        if instantiate_msg.test_mode {
+            "Mock beacon contract instantiated at address:"
+        } else {
+            "Beacon contract instantiated at address:"
+        },
+        theme.highlight.apply_to(&deployed_address)
+    ));
+    let network = config.get_network_mut(&network_name).unwrap().1.unwrap();
+    network.deployed_beacon_address = Some(deployed_address);
+    // Without a known release, the recorded one is left as is rather than forgotten
+    if deployed_version.is_some() {
+        network.deployed_beacon_version = deployed_version;
+    }
+    config
+}
+
+/// Uploads the beacon WASM, or reuses an identical code already stored on chain,
+/// and returns its code id along with the release it belongs to, if known.
+pub async fn store_beacon(
+    pb: &ProgressBar,
+    network: &Network,
+    wallet: &Wallet,
+    wasm_file: Option<impl Into<PathBuf>>,
+    version: Option<&str>,
+    pinned_checksum: Option<&str>,
+) -> (u64, Option<String>) {
+    let (wasm_bytes, version) = beacon_wasm(pb, wasm_file, version, pinned_checksum).await;
+    let code_id = store_code(pb, network, wallet, wasm_bytes, "beacon").await;
+    (code_id, version)
+}
+
+/// Reads the beacon WASM file or, without one, downloads and verifies the release
+/// `version` (or the latest release). Returns the WASM along with the release it
+/// belongs to, if known.
+pub async fn beacon_wasm(
+    pb: &ProgressBar,
+    wasm_file: Option<impl Into<PathBuf>>,
+    version: Option<&str>,
+    pinned_checksum: Option<&str>,
+) -> (Vec<u8>, Option<String>) {
+    let (wasm_file, version) = if let Some(wasm_file) = wasm_file {
+        (wasm_file.into(), None)
+    } else {
+        let (wasm_file, version) = fetch_beacon_wasm(pb, version, pinned_checksum).await;
+        (wasm_file, Some(version))
+    };
+
+    let wasm_bytes = std::fs::read(wasm_file).unwrap_or_else(|err| {
+        pb.set_style(CLITheme::failed_spinner());
+        pb.set_prefix("✗");
+        pb.finish_with_message(format!("{} {}", "Error reading WASM file:", err));
+        std::process::exit(1);
+    });
+    let version = version.or_else(|| known_release(&hex::encode(Sha256::digest(&wasm_bytes))));
+    (wasm_bytes, version)
+}
+
+/// Fetches the metadata of the code with the given id, exiting unless it exists
+/// and the wallet is allowed to instantiate it.
+async fn check_code(
+    pb: &ProgressBar,
+    network: &Network,
+    wallet: &Wallet,
+    code_id: u64,
+) -> CodeInfo {
+    let code = network
+        .fetch_code_info(code_id)
+        .await
+        .unwrap_or_else(|err| {
             pb.set_style(CLITheme::failed_spinner());
             pb.set_prefix("✗");
-            pb.finish_with_message(format!("{} {}", "Error fetching latest release:", err));
+            pb.finish_with_message(format!("Error fetching code {code_id}: {err}"));
             std::process::exit(1);
         });
-        pb.set_message("Downloading latest release...");
-        let download_path = std::env::temp_dir().join("beacon.wasm");
-        download_file(wasm_download_url, download_path)
-            .await
-            .unwrap_or_else(|err| {
-                pb.set_style(CLITheme::failed_spinner());
-                pb.set_prefix("✗");
-                pb.finish_with_message(format!("{} {}", "Error downloading latest release:", err));
-                std::process::exit(1);
-            })
+    if !code.can_instantiate(wallet.address.as_ref()) {
+        pb.set_style(CLITheme::failed_spinner());
+        pb.set_prefix("✗");
+        pb.finish_with_message(format!(
+            "Code {code_id} cannot be instantiated by wallet {}",
+            wallet.address
+        ));
+        std::process::exit(1);
+    }
+    code
+}
+
+/// The release of a beacon WASM with the given checksum, if it is in the cache.
+pub fn known_release(checksum: &str) -> Option<String> {
+    WasmCache::open()
+        .ok()?
+        .list()
+        .ok()?
+        .into_iter()
+        .find(|cached| cached.checksum.eq_ignore_ascii_case(checksum))
+        .map(|cached| cached.tag)
+}
+
+/// Finds the beacon WASM of release `version` (or the latest release) in the
+/// cache, or downloads, verifies and caches it. Returns its path and release tag.
+async fn fetch_beacon_wasm(
+    pb: &ProgressBar,
+    version: Option<&str>,
+    pinned_checksum: Option<&str>,
+) -> (PathBuf, String) {
+    let theme = CLITheme::default();
+    // The cache is an optimization, deploys still work without it.
+    let cache = WasmCache::open().ok();
+    let cached = |tag: &str| {
+        cache
+            .as_ref()
+            .and_then(|cache| cache.get(tag, pinned_checksum).ok().flatten())
     };
-    pb.set_message("Uploading beacon contract...");
+    // A pinned release can be deployed without fetching its release info
+    if let Some(cached) = version.and_then(cached) {
+        pb.println(format!(
+            "{} {}",
+            theme.dimmed.apply_to("Using cached beacon release"),
+            cached.tag
+        ));
+        return (cached.path, cached.tag);
+    }
 
-    let wasm_bytes = std::fs::read(wasm_file).unwrap_or_else(|err| {
+    pb.set_message(match version {
+        Some(version) => format!("Fetching release {version}..."),
+        None => "Fetching latest release...".to_string(),
+    });
+    let release = fetch_release(version).await.unwrap_or_else(|err| {
         pb.set_style(CLITheme::failed_spinner());
         pb.set_prefix("✗");
-        pb.finish_with_message(format!("{} {}", "Error reading WASM file:", err));
+        pb.finish_with_message(format!("{} {}", "Error fetching release:", err));
         std::process::exit(1);
     });
+    if let Some(cached) = cached(&release.tag) {
+        pb.println(format!(
+            "{} {}",
+            theme.dimmed.apply_to("Using cached beacon release"),
+            cached.tag
+        ));
+        return (cached.path, cached.tag);
+    }
 
+    pb.set_message(format!("Downloading release {}...", release.tag));
+    let download = download_release(&release, pinned_checksum)
+        .await
+        .unwrap_or_else(|err| {
+            pb.set_style(CLITheme::failed_spinner());
+            pb.set_prefix("✗");
+            pb.finish_with_message(format!("{} {}", "Error downloading release:", err));
+            std::process::exit(1);
+        });
+    if !download.verified {
+        pb.println(format!(
+            "{} {}",
+            theme.warning.apply_to(format!(
+                "No checksum found for release {}, the download could not be verified. SHA-256:",
+                release.tag
+            )),
+            download.checksum
+        ));
+    }
+    let path = match cache.map(|cache| cache.add(&release.tag, &download.path)) {
+        Some(Ok(cached)) => cached.path,
+        _ => download.path,
+    };
+    (path, release.tag)
+}
+
+/// Uploads `wasm_bytes`, or reuses an identical code already stored on chain that
+/// the wallet can instantiate, and returns its code id.
+pub async fn store_code(
+    pb: &ProgressBar,
+    network: &Network,
+    wallet: &Wallet,
+    wasm_bytes: Vec<u8>,
+    name: &str,
+) -> u64 {
+    pb.set_message(format!(
+        "Looking for an existing upload of the {name} contract..."
+    ));
+    let checksum = hex::encode(Sha256::digest(&wasm_bytes));
+    // Failing to list codes only means the contract is uploaded again.
+    if let Ok(Some(code_id)) = network.find_code(&checksum, wallet.address.as_ref()).await {
+        pb.println(format!(
+            "{} {}",
+            CLITheme::default()
+                .dimmed
+                .apply_to(format!("Reusing uploaded {name} contract, code id")),
+            code_id
+        ));
+        return code_id;
+    }
+
+    pb.set_message(format!("Uploading {name} contract..."));
     let msg = MsgStoreCode {
         sender: wallet.address.clone(),
         wasm_byte_code: wasm_bytes,
@@ -138,7 +290,7 @@ pub async fn deploy_beacon(
         .unwrap_or_else(|err| {
             pb.set_style(CLITheme::failed_spinner());
             pb.set_prefix("✗");
-            pb.finish_with_message(format!("{} {}", "Error uploading beacon contract:", err));
+            pb.finish_with_message(format!("Error uploading {name} contract: {err}"));
             std::process::exit(1);
         });
     pb.set_message("Waiting for transaction to be included in block...");
@@ -152,51 +304,35 @@ pub async fn deploy_beacon(
         std::process::exit(1);
     });
 
-    let res = MsgStoreCodeResponse::try_from(res).unwrap_or_else(|err| {
-        pb.set_style(CLITheme::failed_spinner());
-        pb.set_prefix("✗");
-        pb.finish_with_message(format!(
-            "{} {}",
-            "Error decoding transaction response:", err
-        ));
-        std::process::exit(1);
-    });
-
-    pb.set_message("Instantiating beacon contract in test mode...");
-
-    let subsidized_callbacks = wallet.network.subsidized_callbacks.unwrap_or(false);
-
-    let gas_price =
-        Decimal::from_str(wallet.network.gas_info.gas_price.to_string().as_str()).unwrap();
-
-    let protocol_fee = if subsidized_callbacks {
-        Uint128::zero()
-    } else {
-        Uint128::from(BEACON_BASE_GAS) * gas_price
-    };
-    #[allow(clippy::cast_possible_truncation)]
-    let instantiate_msg = InstantiateMsg {
-        whitelist_deposit_amt: Uint128::zero(),
-        refund_increment_amt: Uint128::zero(),
-        key_activation_delay: 0,
-        protocol_fee: protocol_fee.u128() as u64,
-        submitter_share: 100,
-        native_denom: wallet.network.gas_info.denom.clone(),
-        whitelisted_keys: vec![],
-        belief_gas_price: gas_price,
-        permissioned: false,
-        test_mode: true,
-        subsidize_callbacks: wallet.network.subsidized_callbacks.unwrap_or(false),
-    };
+    MsgStoreCodeResponse::try_from(res)
+        .unwrap_or_else(|err| {
+            pb.set_style(CLITheme::failed_spinner());
+            pb.set_prefix("✗");
+            pb.finish_with_message(format!(
+                "{} {}",
+                "Error decoding transaction response:", err
+            ));
+            std::process::exit(1);
+        })
+        .code_id
+}
 
+/// Instantiates `code_id` with the wallet as admin, and returns the address of
+/// the new contract.
+pub async fn instantiate_contract(
+    pb: &ProgressBar,
+    wallet: &Wallet,
+    code_id: u64,
+    label: String,
+    msg: Vec<u8>,
+    name: &str,
+) -> String {
     let msg = MsgInstantiateContract {
         sender: wallet.address.clone(),
         admin: Some(wallet.address.clone()),
-        code_id: res.code_id,
-        label: Some("Entropy Beacon (MOCK)".to_string()),
-        msg: serde_json::to_string(&instantiate_msg)
-            .unwrap()
-            .into_bytes(),
+        code_id,
+        label: Some(label),
+        msg,
         funds: vec![],
     };
 
@@ -206,10 +342,7 @@ pub async fn deploy_beacon(
         .unwrap_or_else(|err| {
             pb.set_style(CLITheme::failed_spinner());
             pb.set_prefix("✗");
-            pb.finish_with_message(format!(
-                "{} {}",
-                "Error instantiating mock beacon contract:", err
-            ));
+            pb.finish_with_message(format!("Error instantiating {name} contract: {err}"));
             std::process::exit(1);
         });
 
@@ -224,7 +357,7 @@ pub async fn deploy_beacon(
         std::process::exit(1);
     });
 
-    let deployed_address = res.logs[0]
+    res.logs[0]
         .events
         .iter()
         .find(|e| e.type_ == "instantiate")
@@ -234,19 +367,6 @@ pub async fn deploy_beacon(
             pb.set_prefix("✗");
             pb.finish_with_message("Error decoding transaction response.");
             std::process::exit(1);
-        });
-
-    pb.set_style(CLITheme::success_spinner());
-    pb.set_prefix("✓");
-    pb.finish_with_message(format!(
-        "{} {}\n",
-        "Mock beacon contract instantiated at address:",
-        theme.highlight.apply_to(deployed_address)
-    ));
-    config
-        .get_network_mut(&Some(network_name))
-        .unwrap()
-        .1
-        .unwrap()
-        .deployed_beacon_address = Some(deployed_address.to_string());
+        })
+        .clone()
 }
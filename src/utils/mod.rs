@@ -2,7 +2,10 @@ pub mod user_prompts;
 pub mod deploy;
 pub mod theme;
 pub mod config;
+pub mod contracts;
 pub mod beacon_interface;
 pub use theme::CLITheme;
+pub mod wasm_cache;
 pub mod wasm_fetch;
-pub mod webhook;
\ No newline at end of file
+pub mod webhook;
+pub mod vrf;
\ No newline at end of file
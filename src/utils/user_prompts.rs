@@ -84,6 +84,8 @@ pub fn create_network() -> (String, Network) {
                     gas_info,
                     account_info,
                     deployed_beacon_address: None,
+                    deployed_beacon_version: None,
+                    deployed_contracts: None,
                     subsidized_callbacks,
                 },
             )
@@ -0,0 +1,85 @@
+use ecvrf_rs::{encode_hex, Proof, PublicKey};
+use thiserror::Error;
+
+/// Length of an encoded ECVRF-EDWARDS25519 proof.
+const PROOF_LENGTH: usize = 80;
+
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    #[error("Invalid proof: {0}")]
+    Invalid(#[from] ecvrf_rs::errors::VRFError),
+    #[error("Proof is {0} bytes long, expected {PROOF_LENGTH}")]
+    Length(usize),
+    #[error("Proof is signed by {actual}, expected {expected}")]
+    Signer {
+        expected: PublicKey,
+        actual: PublicKey,
+    },
+    #[error("Proof is for entropy {actual}, but the last entropy is {expected}")]
+    Message { expected: String, actual: String },
+}
+
+/// Verifies a proof, optionally checking that it was signed by `public_key` and
+/// chained from `last_entropy`. Returns the entropy the proof produces.
+pub fn verify_proof(
+    proof: &Proof,
+    public_key: Option<&PublicKey>,
+    last_entropy: Option<&[u8]>,
+) -> Result<[u8; 64], VerifyError> {
+    if let Some(public_key) = public_key {
+        if &proof.signer != public_key {
+            return Err(VerifyError::Signer {
+                expected: *public_key,
+                actual: proof.signer,
+            });
+        }
+    }
+    if let Some(last_entropy) = last_entropy {
+        if proof.message_bytes != last_entropy {
+            return Err(VerifyError::Message {
+                expected: encode_hex(last_entropy),
+                actual: encode_hex(&proof.message_bytes),
+            });
+        }
+    }
+    // The proof decoder panics on short input instead of returning an error
+    if proof.proof_bytes.len() != PROOF_LENGTH {
+        return Err(VerifyError::Length(proof.proof_bytes.len()));
+    }
+    Ok(proof.verify()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::beacon_interface::{test_pk, test_sk};
+
+    #[test]
+    fn proofs_are_checked_against_signer_and_entropy() {
+        let proof = Proof::new(&test_sk(), [1u8; 64]).unwrap();
+        assert!(verify_proof(&proof, Some(&test_pk()), Some(&[1u8; 64])).is_ok());
+        assert!(matches!(
+            verify_proof(&proof, None, Some(&[2u8; 64])),
+            Err(VerifyError::Message { .. })
+        ));
+
+        let other = ecvrf_rs::SecretKey::new(&[7u8; 32]);
+        let other_pk = other.extract_public_key_and_scalar().unwrap().0;
+        assert!(matches!(
+            verify_proof(&proof, Some(&other_pk), None),
+            Err(VerifyError::Signer { .. })
+        ));
+
+        let mut tampered = proof;
+        tampered.proof_bytes[0] ^= 1;
+        assert!(matches!(
+            verify_proof(&tampered, None, None),
+            Err(VerifyError::Invalid(_))
+        ));
+        tampered.proof_bytes.truncate(1);
+        assert!(matches!(
+            verify_proof(&tampered, None, None),
+            Err(VerifyError::Length(1))
+        ));
+    }
+}
@@ -0,0 +1,196 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Environment variable overriding the cache directory.
+pub const CACHE_DIR_ENV: &str = "ENTROPY_CACHE_DIR";
+/// Bytes every WASM module starts with.
+pub const WASM_MAGIC: &[u8] = b"\0asm";
+
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("IO error: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Could not find a cache directory, set {CACHE_DIR_ENV} to choose one")]
+    NoCacheDir,
+    #[error("Invalid release tag {0}")]
+    InvalidTag(String),
+    #[error("{0} is not a WASM file")]
+    NotWasm(String),
+}
+
+/// A beacon WASM stored in the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedWasm {
+    pub tag: String,
+    pub checksum: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Cache of beacon WASM artifacts, stored as `<root>/beacon/<tag>/<sha256>.wasm`.
+#[derive(Debug, Clone)]
+pub struct WasmCache {
+    root: PathBuf,
+}
+
+impl WasmCache {
+    /// Opens the cache in `ENTROPY_CACHE_DIR`, or the user's cache directory.
+    pub fn open() -> Result<Self, CacheError> {
+        let root = if let Some(dir) = std::env::var_os(CACHE_DIR_ENV) {
+            PathBuf::from(dir)
+        } else if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+            PathBuf::from(dir).join("entropycli")
+        } else if let Some(dir) = std::env::var_os("LOCALAPPDATA") {
+            PathBuf::from(dir).join("entropycli")
+        } else if let Some(dir) = std::env::var_os("HOME") {
+            PathBuf::from(dir).join(".cache").join("entropycli")
+        } else {
+            return Err(CacheError::NoCacheDir);
+        };
+        Ok(Self::at(root))
+    }
+
+    pub fn at(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn beacon_dir(&self) -> PathBuf {
+        self.root.join("beacon")
+    }
+
+    fn tag_dir(&self, tag: &str) -> Result<PathBuf, CacheError> {
+        if tag.is_empty() || tag.starts_with('.') || tag.contains(['/', '\\']) {
+            return Err(CacheError::InvalidTag(tag.to_string()));
+        }
+        Ok(self.beacon_dir().join(tag))
+    }
+
+    /// Lists the cached artifacts, sorted by tag.
+    pub fn list(&self) -> Result<Vec<CachedWasm>, CacheError> {
+        let mut cached = vec![];
+        let Ok(tags) = std::fs::read_dir(self.beacon_dir()) else {
+            return Ok(cached);
+        };
+        for tag in tags {
+            let tag = tag?;
+            if !tag.file_type()?.is_dir() {
+                continue;
+            }
+            for file in std::fs::read_dir(tag.path())? {
+                let path = file?.path();
+                let Some(checksum) = path
+                    .file_stem()
+                    .filter(|_| path.extension().is_some_and(|ext| ext == "wasm"))
+                else {
+                    continue;
+                };
+                cached.push(CachedWasm {
+                    tag: tag.file_name().to_string_lossy().to_string(),
+                    checksum: checksum.to_string_lossy().to_string(),
+                    size: std::fs::metadata(&path)?.len(),
+                    path,
+                });
+            }
+        }
+        cached.sort_by(|a, b| a.tag.cmp(&b.tag).then_with(|| a.checksum.cmp(&b.checksum)));
+        Ok(cached)
+    }
+
+    /// Finds a cached artifact of `tag`, with the given checksum if set. Artifacts
+    /// that no longer match their checksum are removed.
+    pub fn get(&self, tag: &str, checksum: Option<&str>) -> Result<Option<CachedWasm>, CacheError> {
+        self.tag_dir(tag)?;
+        for cached in self.list()?.into_iter().filter(|c| c.tag == tag) {
+            if checksum.is_some_and(|checksum| !checksum.eq_ignore_ascii_case(&cached.checksum)) {
+                continue;
+            }
+            if hex::encode(Sha256::digest(std::fs::read(&cached.path)?)) == cached.checksum {
+                return Ok(Some(cached));
+            }
+            std::fs::remove_file(&cached.path)?;
+        }
+        Ok(None)
+    }
+
+    /// Copies the WASM at `file` into the cache as release `tag`.
+    pub fn add(&self, tag: &str, file: &Path) -> Result<CachedWasm, CacheError> {
+        let dir = self.tag_dir(tag)?;
+        let bytes = std::fs::read(file)?;
+        if !bytes.starts_with(WASM_MAGIC) {
+            return Err(CacheError::NotWasm(file.display().to_string()));
+        }
+        let checksum = hex::encode(Sha256::digest(&bytes));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{checksum}.wasm"));
+        std::fs::write(&path, &bytes)?;
+        Ok(CachedWasm {
+            tag: tag.to_string(),
+            checksum,
+            path,
+            size: bytes.len() as u64,
+        })
+    }
+
+    /// Removes the cached artifacts of `tag`, or all of them, and returns how many were removed.
+    pub fn clear(&self, tag: Option<&str>) -> Result<usize, CacheError> {
+        let (dir, count) = match tag {
+            Some(tag) => (
+                self.tag_dir(tag)?,
+                self.list()?.iter().filter(|c| c.tag == tag).count(),
+            ),
+            None => (self.beacon_dir(), self.list()?.len()),
+        };
+        if dir.exists() {
+            std::fs::remove_dir_all(dir)?;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn artifacts_are_cached_by_tag_and_checksum() {
+        let root = std::env::temp_dir().join(format!("entropycli-cache-{}", std::process::id()));
+        let cache = WasmCache::at(&root);
+        let file = root.join("beacon.wasm");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(&file, b"\0asm\x01\0\0\0").unwrap();
+
+        let cached = cache.add("v1.0.0", &file).unwrap();
+        assert_eq!(cache.list().unwrap(), vec![cached.clone()]);
+        assert_eq!(cache.get("v1.0.0", None).unwrap(), Some(cached.clone()));
+        assert_eq!(
+            cache
+                .get("v1.0.0", Some(&cached.checksum.to_uppercase()))
+                .unwrap(),
+            Some(cached.clone())
+        );
+        assert_eq!(cache.get("v1.0.0", Some("00")).unwrap(), None);
+        assert_eq!(cache.get("v2.0.0", None).unwrap(), None);
+        assert!(matches!(
+            cache.get("../v1.0.0", None),
+            Err(CacheError::InvalidTag(_))
+        ));
+
+        // Corrupted artifacts are dropped
+        std::fs::write(&cached.path, b"\0asm").unwrap();
+        assert_eq!(cache.get("v1.0.0", None).unwrap(), None);
+
+        std::fs::write(&file, b"not wasm").unwrap();
+        assert!(matches!(
+            cache.add("v1.0.0", &file),
+            Err(CacheError::NotWasm(_))
+        ));
+
+        std::fs::write(&file, b"\0asm\x01\0\0\0").unwrap();
+        cache.add("v1.0.0", &file).unwrap();
+        assert_eq!(cache.clear(None).unwrap(), 1);
+        assert!(cache.list().unwrap().is_empty());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
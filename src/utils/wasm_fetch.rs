@@ -1,38 +1,178 @@
 use reqwest::Client;
+use sha2::{Digest, Sha256};
 use std::{io::Cursor, path::PathBuf};
 use thiserror::Error;
 
-pub const LATEST_RELEASE_URL: &str =
-    "https://api.github.com/repos/EntropicLabs/entropy_beacon_contracts/releases/latest";
+use super::wasm_cache::WASM_MAGIC;
+
+pub const RELEASES_URL: &str =
+    "https://api.github.com/repos/EntropicLabs/entropy_beacon_contracts/releases";
+/// Name of the release asset listing the SHA-256 checksums of the other assets.
+pub const CHECKSUMS_ASSET: &str = "checksums.txt";
 
 #[derive(Debug, Error)]
 pub enum FetchError {
-    #[error("Failed to fetch latest release info")]
+    #[error("Failed to fetch release info")]
     ReleaseInfo(reqwest::Error),
-    #[error("Failed to parse latest release info")]
+    #[error("Failed to parse release info")]
     ResponseJSON(reqwest::Error),
-    #[error("Failed to parse latest release info")]
+    #[error("Failed to parse release info")]
     ParseJSON(),
+    #[error("Release {0} not found")]
+    ReleaseNotFound(String),
+    #[error("No beacon WASM found in release {0}")]
+    AssetNotFound(String),
     #[error("IO error")]
     IO(std::io::Error),
-    #[error("Failed to download latest release")]
+    #[error("Failed to download release")]
     Download(reqwest::Error),
+    #[error("Checksum mismatch for {name}, expected {expected} but got {actual}")]
+    ChecksumMismatch {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Downloaded {0} is not a WASM file")]
+    NotWasm(String),
+}
+
+/// A beacon release, with the download URLs of its WASM and checksums.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub tag: String,
+    pub wasm_name: String,
+    pub wasm_url: String,
+    pub checksums_url: Option<String>,
+}
+
+/// A downloaded beacon WASM.
+#[derive(Debug, Clone)]
+pub struct Download {
+    pub path: PathBuf,
+    pub checksum: String,
+    /// Whether the checksum matched a pinned or published checksum
+    pub verified: bool,
 }
 
-pub async fn fetch_release_url() -> Result<String, FetchError> {
+/// Fetches the release with the given tag, or the latest release.
+pub async fn fetch_release(tag: Option<&str>) -> Result<Release, FetchError> {
     let client = Client::new();
+    let url = match tag {
+        Some(tag) => format!("{RELEASES_URL}/tags/{tag}"),
+        None => format!("{RELEASES_URL}/latest"),
+    };
 
     let response = client
-        .get(LATEST_RELEASE_URL)
+        .get(url)
         .header("User-Agent", "entropycli")
         .send()
         .await
         .map_err(FetchError::ReleaseInfo)?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(FetchError::ReleaseNotFound(
+            tag.unwrap_or("latest").to_string(),
+        ));
+    }
     let json: serde_json::Value = response.json().await.map_err(FetchError::ResponseJSON)?;
-    let wasm_download_url = json["assets"][0]["browser_download_url"]
-        .as_str()
-        .ok_or(FetchError::ParseJSON())?;
-    Ok(wasm_download_url.to_string())
+    parse_release(&json)
+}
+
+fn parse_release(json: &serde_json::Value) -> Result<Release, FetchError> {
+    let tag = json["tag_name"].as_str().ok_or(FetchError::ParseJSON())?;
+    let assets = json["assets"].as_array().ok_or(FetchError::ParseJSON())?;
+    let asset = |name: &str| {
+        assets
+            .iter()
+            .find(|a| a["name"] == name)
+            .and_then(|a| a["browser_download_url"].as_str())
+            .map(ToString::to_string)
+    };
+    // Prefer the beacon contract if the release ships several contracts
+    let wasm_names = assets
+        .iter()
+        .filter_map(|a| a["name"].as_str())
+        .filter(|name| {
+            std::path::Path::new(name)
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wasm"))
+        })
+        .collect::<Vec<_>>();
+    let wasm_name = wasm_names
+        .iter()
+        .find(|name| name.contains("beacon"))
+        .or_else(|| wasm_names.first().filter(|_| wasm_names.len() == 1))
+        .ok_or_else(|| FetchError::AssetNotFound(tag.to_string()))?;
+
+    Ok(Release {
+        tag: tag.to_string(),
+        wasm_name: (*wasm_name).to_string(),
+        wasm_url: asset(wasm_name).ok_or(FetchError::ParseJSON())?,
+        checksums_url: asset(CHECKSUMS_ASSET),
+    })
+}
+
+/// Finds the checksum of `name` in a `sha256sum` style checksums file.
+fn find_checksum(checksums: &str, name: &str) -> Option<String> {
+    checksums.lines().find_map(|line| {
+        let (checksum, file) = line.split_once(char::is_whitespace)?;
+        (file.trim().trim_start_matches('*') == name).then(|| checksum.to_lowercase())
+    })
+}
+
+/// Downloads the WASM of `release`, verifying it against the `pinned` checksum,
+/// or the checksum published with the release.
+pub async fn download_release(
+    release: &Release,
+    pinned: Option<&str>,
+) -> Result<Download, FetchError> {
+    let client = Client::new();
+    let expected = match (pinned, &release.checksums_url) {
+        (Some(pinned), _) => Some(pinned.to_lowercase()),
+        (None, Some(url)) => {
+            let checksums = client
+                .get(url)
+                .header("User-Agent", "entropycli")
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(FetchError::Download)?
+                .text()
+                .await
+                .map_err(FetchError::Download)?;
+            find_checksum(&checksums, &release.wasm_name)
+        }
+        (None, None) => None,
+    };
+
+    // Release tags come from the API, so they are kept out of the path. The name is
+    // unique to this process, so concurrent downloads don't clobber each other.
+    let path = std::env::temp_dir().join(format!("entropycli-beacon-{}.wasm", std::process::id()));
+    download_file(release.wasm_url.clone(), path.clone()).await?;
+    let bytes = tokio::fs::read(&path).await.map_err(FetchError::IO)?;
+    let checksum = hex::encode(Sha256::digest(&bytes));
+    if let Some(expected) = &expected {
+        if *expected != checksum {
+            tokio::fs::remove_file(&path)
+                .await
+                .map_err(FetchError::IO)?;
+            return Err(FetchError::ChecksumMismatch {
+                name: release.wasm_name.clone(),
+                expected: expected.clone(),
+                actual: checksum,
+            });
+        }
+    } else if !bytes.starts_with(WASM_MAGIC) {
+        // Without a checksum, at least make sure an error page wasn't saved as the WASM
+        tokio::fs::remove_file(&path)
+            .await
+            .map_err(FetchError::IO)?;
+        return Err(FetchError::NotWasm(release.wasm_name.clone()));
+    }
+    Ok(Download {
+        path,
+        checksum,
+        verified: expected.is_some(),
+    })
 }
 
 pub async fn download_file(url: String, path: PathBuf) -> Result<PathBuf, FetchError> {
@@ -41,6 +181,7 @@ pub async fn download_file(url: String, path: PathBuf) -> Result<PathBuf, FetchE
         .get(&url)
         .send()
         .await
+        .and_then(reqwest::Response::error_for_status)
         .map_err(FetchError::Download)?;
     let mut file = tokio::fs::File::create(path.clone())
         .await
@@ -51,3 +192,48 @@ pub async fn download_file(url: String, path: PathBuf) -> Result<PathBuf, FetchE
         .map_err(FetchError::IO)?;
     Ok(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_assets_are_selected_by_name() {
+        let asset = |name: &str| {
+            serde_json::json!({
+                "name": name,
+                "browser_download_url": format!("https://example.com/{name}"),
+            })
+        };
+        let release = parse_release(&serde_json::json!({
+            "tag_name": "v1.0.0",
+            "assets": [
+                asset("checksums.txt"),
+                asset("entropy_example_consumer.wasm"),
+                asset("entropy_beacon.wasm"),
+            ],
+        }))
+        .unwrap();
+        assert_eq!(release.wasm_name, "entropy_beacon.wasm");
+        assert_eq!(release.wasm_url, "https://example.com/entropy_beacon.wasm");
+        assert_eq!(
+            release.checksums_url.as_deref(),
+            Some("https://example.com/checksums.txt")
+        );
+
+        let checksums = "ABC123  entropy_example_consumer.wasm\ndef456 *entropy_beacon.wasm\n";
+        assert_eq!(
+            find_checksum(checksums, "entropy_beacon.wasm").as_deref(),
+            Some("def456")
+        );
+        assert_eq!(find_checksum(checksums, "other.wasm"), None);
+
+        assert!(matches!(
+            parse_release(&serde_json::json!({
+                "tag_name": "v1.0.0",
+                "assets": [asset("a.wasm"), asset("b.wasm")],
+            })),
+            Err(FetchError::AssetNotFound(_))
+        ));
+    }
+}